@@ -0,0 +1,45 @@
+use std::{env, fs, path::Path};
+
+/// The dependencies whose resolved versions are shown in the footer,
+/// as (package name, environment variable).
+const VERSIONS: [(&str, &str); 2] = [
+    ("pest_meta", "PEST_META_VERSION"),
+    ("pest_vm", "PEST_VM_VERSION"),
+];
+
+/// Returns the version of the package locked in the `Cargo.lock` content.
+fn locked_version<'a>(lock: &'a str, package: &str) -> Option<&'a str> {
+    let name = format!("name = \"{}\"", package);
+    let mut lines = lock.lines();
+    while let Some(line) = lines.next() {
+        if line.trim() == name {
+            return lines
+                .next()?
+                .trim()
+                .strip_prefix("version = \"")?
+                .strip_suffix('"');
+        }
+    }
+    None
+}
+
+fn main() {
+    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap_or_default();
+    let lock_path = Path::new(&manifest_dir).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock_path.display());
+    println!("cargo:rerun-if-changed=build.rs");
+    // the grammar, input and rule the debugger opens with (read with `option_env!`)
+    for var in [
+        "PEST_DEBUG_DEFAULT_GRAMMAR",
+        "PEST_DEBUG_DEFAULT_INPUT",
+        "PEST_DEBUG_DEFAULT_RULE",
+    ] {
+        println!("cargo:rerun-if-env-changed={}", var);
+    }
+    // e.g. when built as a dependency without its own lock file
+    let lock = fs::read_to_string(&lock_path).unwrap_or_default();
+    for (package, var) in VERSIONS {
+        let version = locked_version(&lock, package).unwrap_or("unknown");
+        println!("cargo:rustc-env={}={}", var, version);
+    }
+}
@@ -0,0 +1,563 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+
+use pest_meta::{
+    optimizer::{OptimizedExpr, OptimizedRule},
+    parser::{self, Rule},
+    validator,
+};
+
+use crate::{debugworker::RuleModifier, trace};
+
+/// A rule that is referenced in a grammar, but neither defined nor built-in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UndefinedRule {
+    /// the rule name
+    pub name: String,
+    /// the name and (1-based) line and column of every use of the rule
+    pub usages: Vec<(String, usize, usize)>,
+}
+
+/// Returns the undefined rules referenced in the grammar,
+/// in the order they are first used.
+/// It returns nothing if the grammar can't be parsed.
+pub fn undefined_rules(grammar: &str) -> Vec<UndefinedRule> {
+    let Ok(pairs) = parser::parse(Rule::grammar_rules, grammar) else {
+        return vec![];
+    };
+    let rules = pairs
+        .filter(|pair| pair.as_rule() == Rule::grammar_rule)
+        .filter_map(|pair| {
+            let mut inner = pair.into_inner();
+            let name = inner.next()?;
+            (name.as_rule() == Rule::identifier).then_some((name, inner))
+        })
+        .collect::<Vec<_>>();
+    let definitions = rules.iter().map(|(name, _)| name.as_span()).collect();
+    let mut undefined: Vec<UndefinedRule> = vec![];
+    for (name, inner) in rules {
+        for called in inner
+            .flatten()
+            .filter(|pair| pair.as_rule() == Rule::identifier)
+        {
+            let span = called.as_span();
+            if validator::validate_undefined(&definitions, &vec![span]).is_empty() {
+                continue;
+            }
+            let (line, col) = span.start_pos().line_col();
+            let usage = (name.as_str().to_owned(), line, col);
+            match undefined.iter_mut().find(|rule| rule.name == span.as_str()) {
+                Some(rule) => rule.usages.push(usage),
+                None => undefined.push(UndefinedRule {
+                    name: span.as_str().to_owned(),
+                    usages: vec![usage],
+                }),
+            }
+        }
+    }
+    undefined
+}
+
+/// Returns the names and modifiers of the rules defined in the grammar, in the source order
+/// (the optimizer doesn't guarantee to keep it).
+/// It returns nothing if the grammar can't be parsed.
+pub fn rules(grammar: &str) -> Vec<(String, RuleModifier)> {
+    let Ok(pairs) = parser::parse(Rule::grammar_rules, grammar) else {
+        return vec![];
+    };
+    pairs
+        .filter(|pair| pair.as_rule() == Rule::grammar_rule)
+        .filter_map(|pair| {
+            let mut inner = pair.into_inner();
+            let name = inner.next()?;
+            if name.as_rule() != Rule::identifier {
+                return None;
+            }
+            // the modifier (if any) follows the assignment operator
+            let modifier = match inner.nth(1)?.as_rule() {
+                Rule::silent_modifier => RuleModifier::Silent,
+                Rule::atomic_modifier => RuleModifier::Atomic,
+                Rule::compound_atomic_modifier => RuleModifier::CompoundAtomic,
+                Rule::non_atomic_modifier => RuleModifier::NonAtomic,
+                _ => RuleModifier::Normal,
+            };
+            Some((name.as_str().to_owned(), modifier))
+        })
+        .collect()
+}
+
+/// Formats the undefined rules as a single message.
+pub fn undefined_rules_message(undefined: &[UndefinedRule]) -> String {
+    let mut message = String::from("undefined rules:");
+    for UndefinedRule { name, usages } in undefined {
+        // groups the usages by the rule using them
+        let mut users = BTreeMap::<&str, Vec<String>>::new();
+        for (user, line, col) in usages {
+            users
+                .entry(user)
+                .or_default()
+                .push(format!("line {}, col {}", line, col));
+        }
+        let users = users
+            .into_iter()
+            .map(|(user, positions)| format!("{} ({})", user, positions.join("; ")))
+            .collect::<Vec<_>>()
+            .join(", ");
+        message.push_str(&format!("\n- {} is used in {}", name, users));
+    }
+    message
+}
+
+/// Whether the expression can match without consuming any input,
+/// given which rules can.
+fn is_nullable(expr: &OptimizedExpr, nullable: &HashSet<&str>) -> bool {
+    match expr {
+        OptimizedExpr::Str(string) | OptimizedExpr::Insens(string) => string.is_empty(),
+        OptimizedExpr::Range(..) => false,
+        OptimizedExpr::Ident(rule) => {
+            nullable.contains(rule.as_str())
+                || matches!(
+                    rule.as_str(),
+                    "SOI" | "EOI" | "PEEK" | "PEEK_ALL" | "POP" | "POP_ALL" | "DROP"
+                )
+        }
+        OptimizedExpr::Seq(lhs, rhs) => is_nullable(lhs, nullable) && is_nullable(rhs, nullable),
+        OptimizedExpr::Choice(lhs, rhs) => is_nullable(lhs, nullable) || is_nullable(rhs, nullable),
+        OptimizedExpr::RepOnce(expr)
+        | OptimizedExpr::Push(expr)
+        | OptimizedExpr::NodeTag(expr, _)
+        | OptimizedExpr::RestoreOnErr(expr) => is_nullable(expr, nullable),
+        OptimizedExpr::PeekSlice(..)
+        | OptimizedExpr::PosPred(_)
+        | OptimizedExpr::NegPred(_)
+        | OptimizedExpr::Opt(_)
+        | OptimizedExpr::Rep(_)
+        | OptimizedExpr::Skip(_)
+        | OptimizedExpr::PushLiteral(_) => true,
+    }
+}
+
+/// Collects the rules that can be called by the expression
+/// before any input is consumed.
+fn leftmost_calls<'a>(
+    expr: &'a OptimizedExpr,
+    nullable: &HashSet<&str>,
+    calls: &mut HashSet<&'a str>,
+) {
+    match expr {
+        OptimizedExpr::Ident(rule) => {
+            calls.insert(rule);
+        }
+        OptimizedExpr::Seq(lhs, rhs) => {
+            leftmost_calls(lhs, nullable, calls);
+            if is_nullable(lhs, nullable) {
+                leftmost_calls(rhs, nullable, calls);
+            }
+        }
+        OptimizedExpr::Choice(lhs, rhs) => {
+            leftmost_calls(lhs, nullable, calls);
+            leftmost_calls(rhs, nullable, calls);
+        }
+        OptimizedExpr::PosPred(expr)
+        | OptimizedExpr::NegPred(expr)
+        | OptimizedExpr::Opt(expr)
+        | OptimizedExpr::Rep(expr)
+        | OptimizedExpr::RepOnce(expr)
+        | OptimizedExpr::Push(expr)
+        | OptimizedExpr::NodeTag(expr, _)
+        | OptimizedExpr::RestoreOnErr(expr) => leftmost_calls(expr, nullable, calls),
+        _ => {}
+    }
+}
+
+/// Returns the set of the rules that can match without consuming any input.
+fn nullable_set(ast: &[OptimizedRule]) -> HashSet<&str> {
+    // the nullable rules are found with a fixpoint iteration
+    let mut nullable = HashSet::new();
+    loop {
+        let before = nullable.len();
+        for rule in ast {
+            if is_nullable(&rule.expr, &nullable) {
+                nullable.insert(rule.name.as_str());
+            }
+        }
+        if nullable.len() == before {
+            break;
+        }
+    }
+    nullable
+}
+
+/// Returns the names of the rules that can match without consuming any input
+/// (a common cause of infinite loops when repeated), in the grammar order.
+pub fn nullable_rules(ast: &[OptimizedRule]) -> Vec<String> {
+    let nullable = nullable_set(ast);
+    ast.iter()
+        .filter(|rule| nullable.contains(rule.name.as_str()))
+        .map(|rule| rule.name.clone())
+        .collect()
+}
+
+/// Adds what the expression can begin with to `first`,
+/// given the FIRST sets of the rules (see `first_sets`).
+fn first_of(
+    expr: &OptimizedExpr,
+    rules: &HashMap<&str, BTreeSet<String>>,
+    nullable: &HashSet<&str>,
+    first: &mut BTreeSet<String>,
+) {
+    match expr {
+        OptimizedExpr::Str(string) => {
+            first.extend(string.chars().next().map(|c| format!("{:?}", c)))
+        }
+        OptimizedExpr::Insens(string) => {
+            if let Some(c) = string.chars().next() {
+                first.extend(
+                    c.to_lowercase()
+                        .chain(c.to_uppercase())
+                        .map(|c| format!("{:?}", c)),
+                );
+            }
+        }
+        OptimizedExpr::Range(start, end) => {
+            first.insert(format!("'{}'..'{}'", start, end));
+        }
+        OptimizedExpr::Ident(rule) => match rules.get(rule.as_str()) {
+            Some(rule_first) => first.extend(rule_first.iter().cloned()),
+            // these match without consuming any input
+            None if matches!(rule.as_str(), "SOI" | "DROP") => {}
+            // e.g. `ASCII_DIGIT`, `ANY` or `EOI`
+            None => {
+                first.insert(rule.clone());
+            }
+        },
+        OptimizedExpr::PeekSlice(..) => {
+            first.insert("PEEK[..]".to_owned());
+        }
+        // e.g. `(!"a" ~ ANY)*`, which can begin with (almost) any character
+        OptimizedExpr::Skip(_) => {
+            first.insert("ANY".to_owned());
+        }
+        OptimizedExpr::Seq(lhs, rhs) => {
+            first_of(lhs, rules, nullable, first);
+            if is_nullable(lhs, nullable) {
+                first_of(rhs, rules, nullable, first);
+            }
+        }
+        OptimizedExpr::Choice(lhs, rhs) => {
+            first_of(lhs, rules, nullable, first);
+            first_of(rhs, rules, nullable, first);
+        }
+        OptimizedExpr::Opt(expr)
+        | OptimizedExpr::Rep(expr)
+        | OptimizedExpr::RepOnce(expr)
+        | OptimizedExpr::Push(expr)
+        | OptimizedExpr::NodeTag(expr, _)
+        | OptimizedExpr::RestoreOnErr(expr) => first_of(expr, rules, nullable, first),
+        // the predicates and `PUSH_LITERAL` don't consume any input
+        OptimizedExpr::PosPred(_) | OptimizedExpr::NegPred(_) | OptimizedExpr::PushLiteral(_) => {}
+    }
+}
+
+/// Returns the FIRST set of each rule, in the grammar order:
+/// the characters (e.g. `'a'`), ranges (e.g. `'a'..'z'`) and built-in rules
+/// (e.g. `ASCII_DIGIT` or `EOI`) a match of the rule can begin with.
+/// The implicit `WHITESPACE` and `COMMENT` between the tokens aren't included.
+pub fn first_sets(ast: &[OptimizedRule]) -> Vec<(String, Vec<String>)> {
+    let nullable = nullable_set(ast);
+    // the sets only grow, so they're computed with a fixpoint iteration
+    let mut rules: HashMap<&str, BTreeSet<String>> = ast
+        .iter()
+        .map(|rule| (rule.name.as_str(), BTreeSet::new()))
+        .collect();
+    loop {
+        let mut changed = false;
+        for rule in ast {
+            let mut first = BTreeSet::new();
+            first_of(&rule.expr, &rules, &nullable, &mut first);
+            if first.len() > rules[rule.name.as_str()].len() {
+                rules.insert(&rule.name, first);
+                changed = true;
+            }
+        }
+        if !changed {
+            break;
+        }
+    }
+    ast.iter()
+        .map(|rule| {
+            let first = rules.remove(rule.name.as_str()).unwrap_or_default();
+            (rule.name.clone(), first.into_iter().collect())
+        })
+        .collect()
+}
+
+/// Returns the names of the rules that can call themselves
+/// without consuming any input (i.e. they are left-recursive),
+/// in the grammar order.
+///
+/// `pest_meta` rejects most of them, but not the recursion
+/// after a stack operation such as `PEEK` (which matches nothing
+/// when the stack is empty), so this finds the remaining ones.
+pub fn left_recursive_rules(ast: &[OptimizedRule]) -> Vec<String> {
+    let nullable = nullable_set(ast);
+    let calls: HashMap<&str, HashSet<&str>> = ast
+        .iter()
+        .map(|rule| {
+            let mut calls = HashSet::new();
+            leftmost_calls(&rule.expr, &nullable, &mut calls);
+            (rule.name.as_str(), calls)
+        })
+        .collect();
+    ast.iter()
+        .filter(|rule| {
+            // a depth-first search for a path back to the rule
+            let mut visited = HashSet::new();
+            let mut stack = vec![rule.name.as_str()];
+            while let Some(current) = stack.pop() {
+                for callee in calls.get(current).into_iter().flatten() {
+                    if *callee == rule.name {
+                        return true;
+                    }
+                    if visited.insert(*callee) {
+                        stack.push(callee);
+                    }
+                }
+            }
+            false
+        })
+        .map(|rule| rule.name.clone())
+        .collect()
+}
+
+/// The built-in rules that `pest_vm` matches without looking up the grammar,
+/// while `pest_derive` only generates them if the grammar doesn't define them.
+/// (The other built-ins are pest keywords, which can't be defined.)
+const VM_BUILTINS: [&str; 11] = [
+    "ASCII_DIGIT",
+    "ASCII_NONZERO_DIGIT",
+    "ASCII_BIN_DIGIT",
+    "ASCII_OCT_DIGIT",
+    "ASCII_HEX_DIGIT",
+    "ASCII_ALPHA_LOWER",
+    "ASCII_ALPHA_UPPER",
+    "ASCII_ALPHA",
+    "ASCII_ALPHANUMERIC",
+    "ASCII",
+    "NEWLINE",
+];
+
+/// Returns the names of the rules `pest_vm` can't run as the grammar defines them
+/// (it runs its built-in rules of the same names instead), in the grammar order.
+pub fn vm_unsupported_rules(ast: &[OptimizedRule]) -> Vec<String> {
+    ast.iter()
+        .filter(|rule| VM_BUILTINS.contains(&rule.name.as_str()))
+        .map(|rule| rule.name.clone())
+        .collect()
+}
+
+/// Returns the rules referenced by each rule (excluding the built-in ones),
+/// in the grammar order.
+pub fn rule_graph(ast: &[OptimizedRule]) -> Vec<(String, Vec<String>)> {
+    let defined = ast
+        .iter()
+        .map(|rule| rule.name.as_str())
+        .collect::<HashSet<_>>();
+    ast.iter()
+        .map(|rule| {
+            let mut referenced = vec![];
+            trace::visit(&rule.expr, &mut |expr| {
+                if let OptimizedExpr::Ident(name) = expr {
+                    if defined.contains(name.as_str()) && !referenced.contains(name) {
+                        referenced.push(name.clone());
+                    }
+                }
+            });
+            (rule.name.clone(), referenced)
+        })
+        .collect()
+}
+
+/// Assigns the rules of the graph to layers for drawing it:
+/// the rules that aren't referenced by others are in the first layer,
+/// and the other rules are one layer below the first rule (breadth-first)
+/// that references them.
+#[cfg(feature = "web")]
+pub fn graph_layers(graph: &[(String, Vec<String>)]) -> Vec<Vec<&str>> {
+    let referenced = graph
+        .iter()
+        .flat_map(|(rule, callees)| callees.iter().filter(move |callee| *callee != rule))
+        .map(String::as_str)
+        .collect::<HashSet<_>>();
+    let callees = graph
+        .iter()
+        .map(|(rule, callees)| (rule.as_str(), callees))
+        .collect::<HashMap<_, _>>();
+    let mut depths = HashMap::new();
+    let mut queue = graph
+        .iter()
+        .map(|(rule, _)| rule.as_str())
+        .filter(|rule| !referenced.contains(rule))
+        .map(|rule| (rule, 0))
+        .collect::<std::collections::VecDeque<_>>();
+    // the rules only reachable from cycles start new layers from the top
+    let mut rest = graph.iter().map(|(rule, _)| rule.as_str());
+    loop {
+        while let Some((rule, depth)) = queue.pop_front() {
+            if depths.contains_key(rule) {
+                continue;
+            }
+            depths.insert(rule, depth);
+            for callee in callees
+                .get(rule)
+                .into_iter()
+                .flat_map(|callees| callees.iter())
+            {
+                queue.push_back((callee, depth + 1));
+            }
+        }
+        match rest.find(|rule| !depths.contains_key(rule)) {
+            Some(rule) => queue.push_back((rule, 0)),
+            None => break,
+        }
+    }
+    let mut layers: Vec<Vec<&str>> = vec![];
+    for (rule, _) in graph {
+        let depth = depths[rule.as_str()];
+        if layers.len() <= depth {
+            layers.resize(depth + 1, vec![]);
+        }
+        layers[depth].push(rule);
+    }
+    layers
+}
+
+/// Whether a rule is called implicitly (between the tokens of non-atomic rules).
+fn is_implicit(rule: &str) -> bool {
+    rule == "WHITESPACE" || rule == "COMMENT"
+}
+
+/// Returns the rules that aren't referenced by any other rule
+/// (except the implicitly called ones), in the grammar order.
+/// Only one of them is expected to be the grammar's entry rule.
+pub fn unreferenced_rules(graph: &[(String, Vec<String>)]) -> Vec<&str> {
+    let referenced = graph
+        .iter()
+        .flat_map(|(rule, callees)| callees.iter().filter(move |callee| *callee != rule))
+        .map(String::as_str)
+        .collect::<HashSet<_>>();
+    graph
+        .iter()
+        .map(|(rule, _)| rule.as_str())
+        .filter(|rule| !referenced.contains(rule) && !is_implicit(rule))
+        .collect()
+}
+
+/// Returns the rules that can't be reached from the `root` rule
+/// (the implicitly called ones are reachable), in the grammar order.
+/// If the root isn't a grammar rule (e.g. it's a built-in one), none can be reached.
+#[cfg(feature = "web")]
+pub fn unreachable_rules<'a>(graph: &'a [(String, Vec<String>)], root: &str) -> Vec<&'a str> {
+    let callees = graph
+        .iter()
+        .map(|(rule, callees)| (rule.as_str(), callees))
+        .collect::<HashMap<_, _>>();
+    let mut reachable = HashSet::new();
+    let mut stack = vec![root];
+    stack.extend(callees.keys().copied().filter(|rule| is_implicit(rule)));
+    while let Some(rule) = stack.pop() {
+        if reachable.insert(rule) {
+            stack.extend(
+                callees
+                    .get(rule)
+                    .into_iter()
+                    .copied()
+                    .flatten()
+                    .map(String::as_str),
+            );
+        }
+    }
+    graph
+        .iter()
+        .map(|(rule, _)| rule.as_str())
+        .filter(|rule| !reachable.contains(rule))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rule_graph, unreferenced_rules};
+
+    /// Returns the rule graph of the grammar.
+    fn graph(grammar: &str) -> Vec<(String, Vec<String>)> {
+        rule_graph(&pest_meta::parse_and_optimize(grammar).unwrap().1)
+    }
+
+    /// `file` is the entry rule, `dead` is never referenced,
+    /// and `a` and `b` only reference each other (recursively, after consuming input).
+    const GRAMMAR: &str = r##"file = { item ~ ("," ~ item)* }
+item = { ASCII_DIGIT+ }
+dead = { item ~ ";" }
+a = { "a" ~ b? }
+b = { "b" ~ a? }
+WHITESPACE = _{ " " | COMMENT }
+COMMENT = _{ "#" ~ (!"\n" ~ ANY)* }"##;
+
+    #[test]
+    fn rule_references() {
+        let graph = graph(GRAMMAR);
+        // the built-in rules aren't in the graph
+        assert_eq!(graph[1], ("item".to_owned(), vec![]));
+        assert_eq!(
+            graph[5],
+            ("WHITESPACE".to_owned(), vec!["COMMENT".to_owned()])
+        );
+    }
+
+    #[test]
+    fn unreferenced() {
+        let graph = graph(GRAMMAR);
+        // a cycle references its rules, and the implicit rules don't need to be referenced
+        assert_eq!(unreferenced_rules(&graph), ["file", "dead"]);
+        assert_eq!(unreferenced_rules(&[]), [] as [&str; 0]);
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn unreachable() {
+        use super::unreachable_rules;
+
+        let graph = graph(GRAMMAR);
+        // the implicit rules are reachable, unlike a cycle that isn't referenced
+        assert_eq!(unreachable_rules(&graph, "file"), ["dead", "a", "b"]);
+        assert_eq!(unreachable_rules(&graph, "a"), ["file", "item", "dead"]);
+        assert_eq!(unreachable_rules(&graph, "dead"), ["file", "a", "b"]);
+        // e.g. a built-in rule reaches none of the grammar rules
+        assert_eq!(
+            unreachable_rules(&graph, "ASCII_DIGIT"),
+            ["file", "item", "dead", "a", "b"]
+        );
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn layers() {
+        use super::graph_layers;
+
+        // the unreferenced rules are in the first layer, and so is the first rule of the cycle
+        // (which no unreferenced rule reaches), with the rest of the cycle one layer below
+        assert_eq!(
+            graph_layers(&graph(GRAMMAR)),
+            [
+                vec!["file", "dead", "a", "WHITESPACE"],
+                vec!["item", "b", "COMMENT"],
+            ]
+        );
+        // a rule is one layer below the first one that references it
+        assert_eq!(
+            graph_layers(&graph(
+                "a = { \"a\" ~ b }\nb = { c | \"b\" ~ a }\nc = { \"c\" }"
+            )),
+            [vec!["a"], vec!["b"], vec!["c"]]
+        );
+    }
+}
@@ -0,0 +1,4695 @@
+use crate::{
+    analysis,
+    debugworker::{
+        normalize_line_endings, DebuggerEvent, GrammarError, ParseTreeNode, RuleAnalysis,
+        RuleModifier, Worker, WorkerInput, CRLF_WARNING,
+    },
+    diagram::{self, RuleDiagram, Shape},
+    format, highlight,
+    host::{HostCommand, HostEvent},
+    messages::{Lang, Messages},
+    position,
+    share::{self, Session, SharedState},
+    store, ui,
+};
+
+use std::{
+    collections::{HashMap, VecDeque},
+    rc::Rc,
+};
+
+use gloo_events::EventListener;
+use gloo_net::http::Request;
+use gloo_storage::{LocalStorage, Storage};
+use gloo_timers::callback::{Interval, Timeout};
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+
+use web_sys::{
+    Blob, BlobPropertyBag, Element, File, FileReader, HtmlAnchorElement, HtmlDialogElement,
+    HtmlElement, HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement, KeyboardEvent,
+    MessageEvent, Url, UrlSearchParams,
+};
+use yew::prelude::*;
+use yew_agent::{Bridge, Bridged};
+
+/// A breakpoint as displayed in the UI.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Breakpoint {
+    /// whether the breakpoint is ticked
+    pub enabled: bool,
+    /// the rule name
+    pub rule: String,
+    /// the optional (inclusive) position window
+    /// the breakpoint is restricted to
+    pub window: Option<(usize, usize)>,
+    /// the number of hits to ignore before stopping
+    pub ignore: usize,
+}
+
+impl Breakpoint {
+    /// Creates a disabled breakpoint without any position window.
+    pub fn new(rule: String) -> Self {
+        Self {
+            enabled: false,
+            rule,
+            window: None,
+            ignore: 0,
+        }
+    }
+
+    /// The message to send to the worker in order to enable this breakpoint.
+    fn worker_input(&self) -> WorkerInput {
+        match self.window {
+            Some((start, end)) => {
+                WorkerInput::AddConditionalBreakpoint(self.rule.clone(), start, end)
+            }
+            None => WorkerInput::AddBreakpoint(self.rule.clone()),
+        }
+    }
+}
+
+/// The delay (in milliseconds) after the last modification of the grammar or input
+/// before it's sent to the worker.
+const DEBOUNCE_MS: u32 = 300;
+
+/// How long (in milliseconds) the success message is shown.
+const SUCCESS_MS: u32 = 5000;
+
+/// The number of input characters shown on each side of the position
+/// of the current rule in the breakpoint list.
+const SNIPPET_RADIUS: usize = 10;
+
+/// The largest file (in bytes) that can be dropped on the grammar or input pane.
+const MAX_DROPPED_FILE: f64 = 16.0 * 1024.0 * 1024.0;
+
+/// Checks that the file dropped on the pane can be loaded in it:
+/// that it's not too large and, for the grammar, that it's a `.pest` file.
+fn check_dropped_file(pane: Pane, name: &str, size: f64) -> Result<(), String> {
+    if pane == Pane::Grammar && !name.to_lowercase().ends_with(".pest") {
+        return Err(format!(
+            "{} isn't a grammar: only .pest files can be dropped on the grammar",
+            name
+        ));
+    }
+    if size > MAX_DROPPED_FILE {
+        return Err(format!(
+            "{} is too large to load ({:.1} MiB, the limit is {} MiB)",
+            name,
+            size / 1024.0 / 1024.0,
+            MAX_DROPPED_FILE / 1024.0 / 1024.0
+        ));
+    }
+    Ok(())
+}
+
+/// The input size (in bytes) above which a warning is shown,
+/// as highlighting the input may become slow.
+const LARGE_INPUT: usize = 1024 * 1024;
+
+/// Triggers a browser download of `content` as a file named `filename`.
+fn download(filename: &str, content: &str, mime: &str) -> Result<(), JsValue> {
+    let parts = js_sys::Array::of1(&JsValue::from_str(content));
+    let options = BlobPropertyBag::new();
+    options.set_type(mime);
+    let blob = Blob::new_with_str_sequence_and_options(&parts, &options)?;
+    let url = Url::create_object_url_with_blob(&blob)?;
+    let anchor = gloo_utils::document()
+        .create_element("a")?
+        .dyn_into::<HtmlAnchorElement>()?;
+    anchor.set_href(&url);
+    anchor.set_download(filename);
+    anchor.click();
+    Url::revoke_object_url(&url)
+}
+
+/// Reads a file as text and passes the result to `callback` once it's loaded.
+fn read_file(file: &File, callback: Callback<Result<String, String>>) -> Result<(), JsValue> {
+    let reader = FileReader::new()?;
+    let onloadend = {
+        let reader = reader.clone();
+        Closure::once_into_js(move || {
+            let text = reader
+                .result()
+                .ok()
+                .and_then(|result| result.as_string())
+                .ok_or_else(|| "failed to read the file".to_owned());
+            callback.emit(text);
+        })
+    };
+    reader.set_onloadend(Some(onloadend.unchecked_ref()));
+    reader.read_as_text(file)
+}
+
+/// Writes the text to the clipboard and passes whether it succeeded to `callback`
+/// (it fails e.g. without the clipboard permission or outside a secure context).
+fn copy_to_clipboard(text: &str, callback: Callback<bool>) {
+    let navigator = gloo_utils::window().navigator();
+    // `navigator.clipboard` is undefined outside secure contexts
+    let available = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard"))
+        .is_ok_and(|clipboard| !clipboard.is_undefined());
+    if !available {
+        return callback.emit(false);
+    }
+    let onfulfilled = {
+        let callback = callback.clone();
+        Closure::once(move |_: JsValue| callback.emit(true))
+    };
+    let onrejected = Closure::once(move |_: JsValue| callback.emit(false));
+    let _ = navigator
+        .clipboard()
+        .write_text(text)
+        .then2(&onfulfilled, &onrejected);
+    // the closures must outlive the promise (only one of them is called)
+    onfulfilled.forget();
+    onrejected.forget();
+}
+
+/// Fetches a text file from a URL.
+async fn fetch_text(url: &str) -> Result<String, String> {
+    let response = Request::get(url).send().await.map_err(|e| {
+        format!(
+            "failed to fetch {}: {}\n\nThe server may not allow cross-origin requests (CORS) \
+            from this page; raw file URLs (e.g. raw.githubusercontent.com) usually do.",
+            url, e
+        )
+    })?;
+    if !response.ok() {
+        return Err(format!(
+            "failed to fetch {}: {} {}",
+            url,
+            response.status(),
+            response.status_text()
+        ));
+    }
+    response
+        .text()
+        .await
+        .map_err(|e| format!("failed to read {}: {}", url, e))
+}
+
+/// Maps a key press to the message of its keyboard shortcut, if any:
+/// F5 runs, F8 or Enter continues, and Esc restores a maximized pane or stops.
+/// Key presses in form fields are left alone, so that typing isn't hijacked
+/// (except Esc, which still restores a pane if one is maximized), and so are the ones
+/// in an open dialog (e.g. Esc closes a modal one).
+fn shortcut(e: &KeyboardEvent) -> Option<Message> {
+    let target = e
+        .target()
+        .and_then(|target| target.dyn_into::<Element>().ok());
+    if let Some(target) = &target {
+        if target.closest("dialog[open]").ok().flatten().is_some() {
+            return None;
+        }
+    }
+    let tag = target.map(|element| element.tag_name()).unwrap_or_default();
+    if matches!(tag.as_str(), "TEXTAREA" | "INPUT" | "SELECT") {
+        let maximized = gloo_utils::document()
+            .query_selector(".panes > .maximized")
+            .ok()
+            .flatten()
+            .is_some();
+        return (e.key() == "Escape" && maximized).then_some(Message::RestorePane);
+    }
+    match e.key().as_str() {
+        "F5" => Some(Message::Run),
+        "F8" => Some(Message::Continue),
+        // Enter on a focused button, link or summary already activates it
+        "Enter" if !matches!(tag.as_str(), "BUTTON" | "A" | "SUMMARY") => Some(Message::Continue),
+        "Escape" => Some(Message::Escape),
+        _ => None,
+    }
+}
+
+/// The localStorage key of the selected theme.
+const THEME_KEY: &str = "pest-web-debug.theme";
+/// The localStorage key of the UI language.
+const LANG_KEY: &str = "pest-web-debug.lang";
+
+/// The localStorage key of the "skip silent rules" preference.
+const SKIP_SILENT_KEY: &str = "pest-web-debug.skip-silent";
+/// The local storage key of the "normalize line endings" option.
+const NORMALIZE_LINE_ENDINGS_KEY: &str = "pest-web-debug.normalize-line-endings";
+/// The local storage key of the draft (the last edited session),
+/// to recover the edits after a crash or reload.
+const DRAFT_KEY: &str = "pest-web-debug.draft";
+/// The localStorage key of the share of the width given to the grammar pane.
+const SPLIT_RATIO_KEY: &str = "pest-web-debug.split-ratio";
+/// The localStorage key of the indentation of the grammar editor.
+const INDENT_KEY: &str = "pest-web-debug.indent";
+
+/// The textarea to clear once it's confirmed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ClearTarget {
+    Grammar,
+    Input,
+}
+
+/// The column the rule statistics are sorted by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum StatsOrder {
+    /// the number of attempts, descending
+    #[default]
+    Attempts,
+    /// the rule name, ascending
+    Rule,
+}
+
+/// The order the rules are listed in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortMode {
+    /// the order of the rule definitions in the grammar
+    #[default]
+    Definition,
+    /// the alphabetical order of the rule names
+    Alphabetical,
+}
+
+/// The UI color theme.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Theme {
+    /// the default NES.css look
+    #[default]
+    Light,
+    /// light text on a dark background
+    Dark,
+}
+
+impl Theme {
+    /// The other theme.
+    pub fn toggled(self) -> Self {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light,
+        }
+    }
+
+    /// The CSS class of the `#nescss` container.
+    fn class(self) -> &'static str {
+        match self {
+            Theme::Light => "theme-light",
+            Theme::Dark => "theme-dark",
+        }
+    }
+}
+
+/// A pane that can be maximized.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pane {
+    Grammar,
+    Input,
+}
+
+/// The indentation the Tab key inserts in the grammar editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Indent {
+    /// the given number of spaces
+    Spaces(usize),
+    /// a tab character
+    Tab,
+}
+
+impl Default for Indent {
+    /// As in the formatted grammars.
+    fn default() -> Self {
+        Indent::Spaces(4)
+    }
+}
+
+impl Indent {
+    /// The indentations offered by the grammar editor.
+    pub const ALL: [Indent; 3] = [Indent::Spaces(2), Indent::Spaces(4), Indent::Tab];
+
+    /// The text inserted for a level of indentation.
+    pub fn unit(self) -> String {
+        match self {
+            Indent::Spaces(width) => " ".repeat(width),
+            Indent::Tab => "\t".to_owned(),
+        }
+    }
+
+    /// The most spaces removed when dedenting a line
+    /// (a tab is counted as 4 spaces).
+    pub fn width(self) -> usize {
+        match self {
+            Indent::Spaces(width) => width,
+            Indent::Tab => 4,
+        }
+    }
+
+    /// The value of the indentation in the select.
+    fn code(self) -> String {
+        match self {
+            Indent::Spaces(width) => width.to_string(),
+            Indent::Tab => "tab".to_owned(),
+        }
+    }
+}
+
+/// The properties of the web debugger component,
+/// e.g. for embedding it with a preset grammar.
+/// The hard-coded defaults are used for the missing ones.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Properties)]
+pub struct AppProps {
+    /// the initial grammar text
+    #[prop_or_default]
+    pub grammar: Option<String>,
+    /// the initial input text
+    #[prop_or_default]
+    pub input: Option<String>,
+    /// the initial rule to run
+    #[prop_or_default]
+    pub to_run: Option<String>,
+    /// the title in the header
+    #[prop_or_default]
+    pub title: Option<String>,
+    /// the URL of the logo in the header
+    #[prop_or_default]
+    pub logo_url: Option<String>,
+    /// whether to hide the "Thanks" section of the footer
+    #[prop_or_default]
+    pub hide_thanks: bool,
+}
+
+/// Describes the versions of the debugger and of the pest crates it runs the grammars with
+/// (the ones locked at build time).
+fn versions() -> String {
+    format!(
+        "pest-web-debug {} · pest_meta {} · pest_vm {}",
+        env!("CARGO_PKG_VERSION"),
+        env!("PEST_META_VERSION"),
+        env!("PEST_VM_VERSION")
+    )
+}
+
+/// The size (width, height) in pixels of the embedded iframe until changed.
+const DEFAULT_EMBED_SIZE: (u32, u32) = (800, 600);
+/// The smallest and largest width or height of the embedded iframe.
+const EMBED_SIZE_RANGE: (u32, u32) = (200, 4000);
+
+/// The header title when the `title` property isn't set.
+const DEFAULT_TITLE: &str = "pest web debugger";
+/// The header logo when the `logo_url` property isn't set.
+const DEFAULT_LOGO_URL: &str =
+    "https://raw.githubusercontent.com/sbeckeriv/pest_format/master/docs/logo.gif";
+
+/// The state of the web debugger
+/// (initialized from the `AppProps` and the hard-coded defaults).
+pub struct AppState {
+    /// the (unparsed) grammar text from the textarea
+    pub grammar: String,
+    /// the URL to fetch the grammar from
+    pub grammar_url: String,
+    /// the input tabs as (name, content)
+    pub inputs: Vec<(String, String)>,
+    /// the index of the active input tab in `inputs`
+    pub active_input: usize,
+    /// the byte range of the active input that was run,
+    /// if only a selection of it was
+    pub run_selection: Option<(usize, usize)>,
+    /// the name of the file the input was loaded from, if any
+    pub input_file: Option<String>,
+    /// the list of breakpoints
+    pub breakpoints: Vec<Breakpoint>,
+    /// the modifier of each grammar rule
+    pub rule_modifiers: HashMap<String, RuleModifier>,
+    /// whether the breakpoints of silent rules are skipped
+    pub skip_silent: bool,
+    /// whether the spaces, tabs and newlines of the input are shown as glyphs
+    pub show_whitespace: bool,
+    /// whether the CRLF line endings of the inputs are converted to LF
+    pub normalize_line_endings: bool,
+    /// the order the rules are listed in
+    pub sort_mode: SortMode,
+    /// the text the displayed breakpoints are filtered by
+    pub breakpoint_filter: String,
+    /// the list of events to display / go through
+    /// (encountered breakpoints)
+    pub events: VecDeque<DebuggerEvent>,
+    /// the index of the currently displayed event in `events`
+    pub cursor: usize,
+    /// how far (in pixels) the list of events is scrolled
+    pub events_scroll: usize,
+    /// the number of times each rule was hit
+    /// up to (and including) the current event
+    pub hits: HashMap<String, usize>,
+    /// the rule selected to be run
+    pub to_run: String,
+    /// whether the debugger session is currently in progress
+    pub running: bool,
+    /// whether the session is ended as soon as all its events are received
+    /// (i.e. it's run without stopping at the breakpoints)
+    pub run_to_end: bool,
+    /// the rule selected in the "Run to rule" dropdown
+    pub target_rule: String,
+    /// the rule whose first breakpoint the starting session skips to, if any
+    pub run_to_rule: Option<String>,
+    /// the error message, if any
+    pub error: Option<String>,
+    /// the input position where parsing failed, if any
+    pub error_pos: Option<usize>,
+    /// the input position where a successful parse stopped matching,
+    /// if it didn't consume the whole input
+    pub partial_parse: Option<usize>,
+    /// whether the success message of the last run is shown
+    pub success: bool,
+    /// the errors in the grammar, if any
+    pub grammar_errors: Vec<GrammarError>,
+    /// the (not dismissed) warnings about the grammar
+    pub warnings: Vec<String>,
+    /// the names of the sessions saved in the browser storage
+    pub saved_sessions: Vec<String>,
+    /// the name to save the session under (or of the saved session to load or delete)
+    pub session_name: String,
+    /// the draft found at startup that can be restored
+    pub draft: Option<Session>,
+    /// the byte offset of the caret in the input textarea (if it was read)
+    pub input_caret: Option<usize>,
+    /// the textarea to clear if confirmed in the dialog
+    pub confirm_clear: Option<ClearTarget>,
+    /// the rules expected and unexpected where the parsing of the input failed
+    pub expected: Option<(Vec<String>, Vec<String>)>,
+    /// the rule name typed to run instead of the selected one (if not empty)
+    pub typed_rule: String,
+    /// the rule whose FIRST set is shown (the last one clicked)
+    pub first_set_rule: Option<String>,
+    /// the text to copy by hand, as it couldn't be written to the clipboard
+    pub copy_fallback: Option<String>,
+    /// the share link to copy by hand, as it couldn't be written to the clipboard
+    pub share_link_fallback: Option<String>,
+    /// whether the embed code is shown (once "Get embed code" was clicked)
+    pub show_embed: bool,
+    /// the width and height (in pixels) of the embedded iframe
+    pub embed_size: (u32, u32),
+    /// whether the embed code was written to the clipboard, once tried
+    pub embed_copied: Option<bool>,
+    /// the rules referenced by each grammar rule
+    pub rule_graph: Vec<(String, Vec<String>)>,
+    /// the static analysis of the grammar rules
+    pub rule_analysis: RuleAnalysis,
+    /// the railroad diagrams of the grammar rules
+    pub rule_diagrams: Vec<(String, RuleDiagram)>,
+    /// the pretty-printed grammar AST
+    pub ast: Option<String>,
+    /// whether the AST is shown as optimized by pest (or as written)
+    pub ast_optimized: bool,
+    /// the parse result of the last successful run
+    pub parse_tree: Option<Vec<ParseTreeNode>>,
+    /// the time (in milliseconds) the last run's parsing took
+    pub parse_time: Option<f64>,
+    /// the number of breakpoint events per rule in the last run
+    pub attempts: HashMap<String, usize>,
+    /// the column the rule statistics are sorted by
+    pub stats_order: StatsOrder,
+    /// the outcome of each input of the last batch run
+    /// the form is: (input_index, error_message)
+    pub batch_results: Vec<(usize, Option<String>)>,
+    /// the total number of breakpoints hit in the last run,
+    /// if not all of them were collected
+    pub truncated: Option<usize>,
+    /// whether to run the rule to run once the grammar is loaded
+    /// (requested by the `autorun` query parameter)
+    pub autorun: bool,
+    /// the UI color theme
+    pub theme: Theme,
+    /// the UI language
+    pub lang: Lang,
+    /// the share of the width of the grammar and input panes given to the grammar
+    pub split_ratio: f64,
+    /// the indentation the Tab key inserts in the grammar editor
+    pub indent: Indent,
+    /// whether the grammar pane fills the viewport
+    pub grammar_maximized: bool,
+    /// whether the input pane fills the viewport
+    pub input_maximized: bool,
+    /// the delay (in milliseconds) between steps during playback
+    pub playback_speed: u32,
+}
+
+/// The sample grammar the debugger opens with by default.
+const SAMPLE_GRAMMAR: &str = r#"alpha = { 'a'..'z' | 'A'..'Z' }
+
+digit = { '0'..'9' }
+
+ident = { (alpha | digit)+ }
+
+ident_list = _{ !digit ~ ident ~ (" " ~ ident)+ }"#;
+/// The sample input the debugger opens with by default.
+const SAMPLE_INPUT: &str = "hello world";
+/// The rule of the sample grammar to run by default.
+const SAMPLE_RULE: &str = "ident_list";
+
+/// Returns the grammar, input and rule to run the debugger opens with:
+/// the ones given (at build time, see `AppState::default`) or the sample ones.
+/// The rule defaults to the first one of a given grammar.
+fn default_content(
+    grammar: Option<&str>,
+    input: Option<&str>,
+    rule: Option<&str>,
+) -> (String, String, String) {
+    let rule = match (grammar, rule) {
+        (_, Some(rule)) => rule.to_owned(),
+        (Some(grammar), None) => analysis::rules(grammar)
+            .into_iter()
+            .next()
+            .map(|(rule, _)| rule)
+            .unwrap_or_default(),
+        (None, None) => SAMPLE_RULE.to_owned(),
+    };
+    (
+        grammar.unwrap_or(SAMPLE_GRAMMAR).to_owned(),
+        input.unwrap_or(SAMPLE_INPUT).to_owned(),
+        rule,
+    )
+}
+
+/// Returns the breakpoints the debugger opens with: all the rules of the grammar.
+fn default_breakpoints(grammar: &str) -> Vec<Breakpoint> {
+    analysis::rules(grammar)
+        .into_iter()
+        .map(|(rule, _)| Breakpoint::new(rule))
+        .collect()
+}
+
+impl Default for AppState {
+    /// The debugger opens with the sample grammar and input,
+    /// unless others are set at build time by the `PEST_DEBUG_DEFAULT_GRAMMAR`
+    /// and `PEST_DEBUG_DEFAULT_INPUT` environment variables
+    /// (and the rule to run by `PEST_DEBUG_DEFAULT_RULE`).
+    /// All the grammar rules are breakpoints.
+    fn default() -> Self {
+        let (grammar, input, to_run) = default_content(
+            option_env!("PEST_DEBUG_DEFAULT_GRAMMAR"),
+            option_env!("PEST_DEBUG_DEFAULT_INPUT"),
+            option_env!("PEST_DEBUG_DEFAULT_RULE"),
+        );
+        let breakpoints = default_breakpoints(&grammar);
+        Self {
+            grammar,
+            grammar_url: String::new(),
+            inputs: vec![((Lang::default().messages().input_name)(1), input)],
+            active_input: 0,
+            run_selection: None,
+            input_file: None,
+            breakpoints,
+            rule_modifiers: HashMap::new(),
+            skip_silent: false,
+            show_whitespace: false,
+            normalize_line_endings: false,
+            sort_mode: SortMode::default(),
+            breakpoint_filter: String::new(),
+            events: VecDeque::new(),
+            cursor: 0,
+            events_scroll: 0,
+            hits: HashMap::new(),
+            to_run,
+            running: false,
+            run_to_end: false,
+            target_rule: String::new(),
+            run_to_rule: None,
+            error: None,
+            error_pos: None,
+            partial_parse: None,
+            success: false,
+            grammar_errors: vec![],
+            warnings: vec![],
+            saved_sessions: vec![],
+            session_name: String::new(),
+            draft: None,
+            input_caret: None,
+            confirm_clear: None,
+            expected: None,
+            typed_rule: String::new(),
+            first_set_rule: None,
+            copy_fallback: None,
+            share_link_fallback: None,
+            show_embed: false,
+            embed_size: DEFAULT_EMBED_SIZE,
+            embed_copied: None,
+            rule_graph: vec![],
+            rule_analysis: RuleAnalysis::default(),
+            rule_diagrams: vec![],
+            ast: None,
+            ast_optimized: true,
+            parse_tree: None,
+            parse_time: None,
+            attempts: HashMap::new(),
+            stats_order: StatsOrder::default(),
+            batch_results: vec![],
+            truncated: None,
+            autorun: false,
+            theme: Theme::default(),
+            lang: Lang::default(),
+            split_ratio: 0.5,
+            indent: Indent::default(),
+            grammar_maximized: false,
+            input_maximized: false,
+            playback_speed: 500,
+        }
+    }
+}
+
+impl AppState {
+    /// Returns the breakpoints in the order of `sort_mode`.
+    pub fn sorted_breakpoints(&self) -> Vec<&Breakpoint> {
+        let mut breakpoints = self.breakpoints.iter().collect::<Vec<_>>();
+        if self.sort_mode == SortMode::Alphabetical {
+            breakpoints.sort_by(|a, b| a.rule.cmp(&b.rule));
+        }
+        breakpoints
+    }
+
+    /// Whether the breakpoint of the rule is skipped (even if it's enabled),
+    /// as the rule is silent and `skip_silent` is on.
+    pub fn is_skipped(&self, rule: &str) -> bool {
+        self.skip_silent && self.rule_modifiers.get(rule) == Some(&RuleModifier::Silent)
+    }
+
+    /// Returns the (sorted) breakpoints whose rule names match the filter (case-insensitively).
+    pub fn visible_breakpoints(&self) -> impl Iterator<Item = &Breakpoint> {
+        let filter = self.breakpoint_filter.to_lowercase();
+        self.sorted_breakpoints()
+            .into_iter()
+            .filter(move |breakpoint| breakpoint.rule.to_lowercase().contains(&filter))
+    }
+
+    /// Returns the typed rule to run, or else the selected one.
+    pub fn rule_to_run(&self) -> &str {
+        match self.typed_rule.trim() {
+            "" => &self.to_run,
+            typed => typed,
+        }
+    }
+
+    /// Returns the rule from which the reachability is shown: the rule to run,
+    /// unless it's typed and isn't (yet) a grammar rule, in which case it's the selected one.
+    pub fn reachability_root(&self) -> &str {
+        let rule = self.rule_to_run();
+        if self.rule_graph.iter().any(|(name, _)| name == rule) {
+            rule
+        } else {
+            &self.to_run
+        }
+    }
+
+    /// Describes the FIRST set of the clicked rule, if any
+    /// (e.g. `FIRST(digit) = {'0'..'9'}`).
+    pub fn first_set(&self) -> Option<String> {
+        let rule = self.first_set_rule.as_ref()?;
+        let (_, first) = self
+            .rule_analysis
+            .first
+            .iter()
+            .find(|(name, _)| name == rule)?;
+        Some(format!("FIRST({}) = {{{}}}", rule, first.join(", ")))
+    }
+
+    /// Returns whether the grammar has any rule to run.
+    pub fn has_rules(&self) -> bool {
+        !self.breakpoints.is_empty()
+    }
+
+    /// Selects the rule to run by its name.
+    /// Returns `false` (and keeps the selection) if there's no such rule.
+    pub fn select_rule_to_run(&mut self, rule: &str) -> bool {
+        if self
+            .breakpoints
+            .iter()
+            .any(|breakpoint| breakpoint.rule == rule)
+        {
+            self.to_run = rule.to_owned();
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns the part of the state that is exported as a session.
+    pub fn session(&self) -> Session {
+        Session {
+            grammar: self.grammar.clone(),
+            input: self.input().to_owned(),
+            inputs: self.inputs.clone(),
+            active_input: self.active_input,
+            breakpoints: self.breakpoints.clone(),
+            to_run: self.to_run.clone(),
+        }
+    }
+
+    /// Returns the draft if its grammar or inputs differ from the current ones
+    /// (i.e. it has edits that would be lost).
+    pub fn unsaved_draft(&self, draft: Session) -> Option<Session> {
+        let inputs_differ = if draft.inputs.is_empty() {
+            draft.input != self.input()
+        } else {
+            draft.inputs != self.inputs
+        };
+        (draft.grammar != self.grammar || inputs_differ).then_some(draft)
+    }
+
+    /// Returns the HTML of an iframe embedding the debugger with the shared state.
+    pub fn embed_code(&self, props: &AppProps) -> Option<String> {
+        let link = self.shared().link().ok()?;
+        let title = props.title.as_deref().unwrap_or(DEFAULT_TITLE);
+        let (width, height) = self.embed_size;
+        Some(share::embed_code(&link, title, width, height))
+    }
+
+    /// Returns the part of the state that is shared via links.
+    pub fn shared(&self) -> SharedState {
+        SharedState {
+            grammar: self.grammar.clone(),
+            input: self.input().to_owned(),
+            to_run: self.to_run.clone(),
+        }
+    }
+
+    /// Returns the number of rules that were hit in the last run
+    /// and the total number of rules, if there was a run with any hits.
+    /// Only the rules with enabled breakpoints can be hit.
+    pub fn coverage(&self) -> Option<(usize, usize)> {
+        if self.attempts.is_empty() {
+            return None;
+        }
+        let covered = self
+            .breakpoints
+            .iter()
+            .filter(|breakpoint| self.attempts.contains_key(&breakpoint.rule))
+            .count();
+        Some((covered, self.breakpoints.len()))
+    }
+
+    /// Returns the rules with their numbers of attempts, sorted by `stats_order`.
+    pub fn sorted_attempts(&self) -> Vec<(&str, usize)> {
+        let mut attempts = self
+            .attempts
+            .iter()
+            .map(|(rule, count)| (rule.as_str(), *count))
+            .collect::<Vec<_>>();
+        match self.stats_order {
+            StatsOrder::Attempts => {
+                attempts.sort_by(|(a, a_count), (b, b_count)| b_count.cmp(a_count).then(a.cmp(b)))
+            }
+            StatsOrder::Rule => attempts.sort(),
+        }
+        attempts
+    }
+
+    /// Returns the content of the active input tab.
+    pub fn input(&self) -> &str {
+        self.inputs
+            .get(self.active_input)
+            .map_or("", |(_, content)| content)
+    }
+
+    /// Returns the part of the active input that was run.
+    pub fn run_input(&self) -> &str {
+        let input = self.input();
+        self.run_selection
+            .and_then(|(start, end)| input.get(start..end))
+            .unwrap_or(input)
+    }
+
+    /// Replaces the content of the active input tab.
+    /// Its line endings are normalized if `normalize_line_endings` is on.
+    pub fn set_input(&mut self, input: String) {
+        let input = if self.normalize_line_endings {
+            normalize_line_endings(&input)
+        } else {
+            input
+        };
+        match self.inputs.get_mut(self.active_input) {
+            Some((_, content)) => *content = input,
+            None => {
+                self.inputs = vec![((self.lang.messages().input_name)(1), input)];
+                self.active_input = 0;
+            }
+        }
+    }
+
+    /// Converts the CRLF line endings of all inputs to LF.
+    pub fn normalize_inputs(&mut self) {
+        for (_, input) in self.inputs.iter_mut() {
+            *input = normalize_line_endings(input);
+        }
+    }
+
+    /// Moves the cursor to the event at `index`,
+    /// ending the session if there are no more events to stop at.
+    /// Returns whether the parsing succeeded if the session ended,
+    /// or `None` if the debugger is stopped at an event
+    /// (or there's no session with events to step through).
+    pub fn step_to(&mut self, index: usize) -> Option<bool> {
+        if !self.running || self.events.is_empty() {
+            return None;
+        }
+        self.cursor = index;
+        let ended = match self.current_event() {
+            Some(DebuggerEvent::Eof) | None => {
+                // the events only end with `Eof` if the parsing succeeded
+                let success = self.events.back() == Some(&DebuggerEvent::Eof);
+                self.running = false;
+                self.events.clear();
+                self.cursor = 0;
+                Some(success)
+            }
+            _ => None,
+        };
+        self.count_hits();
+        ended
+    }
+
+    /// Moves the cursor to the event at `index` (without ending the session),
+    /// e.g. to go back to an earlier event.
+    /// It returns `false` if there's no such event to stop at.
+    pub fn jump_to(&mut self, index: usize) -> bool {
+        match self.events.get(index) {
+            Some(event) if self.running && *event != DebuggerEvent::Eof => {
+                self.cursor = index;
+                self.count_hits();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Returns the index of the next breakpoint of the rule after the cursor,
+    /// or the number of events (i.e. the end of the session) if it isn't hit again.
+    pub fn next_hit_of(&self, rule: &str) -> usize {
+        (self.cursor + 1..self.events.len())
+            .find(|index| {
+                matches!(&self.events[*index], DebuggerEvent::Breakpoint(hit, ..) if hit == rule)
+            })
+            .unwrap_or(self.events.len())
+    }
+
+    /// Returns the rules the "Run to rule" dropdown offers,
+    /// i.e. the ones whose breakpoints are sent to the worker.
+    pub fn run_to_rule_targets(&self) -> Vec<&str> {
+        self.breakpoints
+            .iter()
+            .filter(|breakpoint| breakpoint.enabled && !self.is_skipped(&breakpoint.rule))
+            .map(|breakpoint| breakpoint.rule.as_str())
+            .collect()
+    }
+
+    /// Returns the event the debugger is currently stopped at, if any.
+    pub fn current_event(&self) -> Option<&DebuggerEvent> {
+        self.events.get(self.cursor)
+    }
+
+    /// Returns the rule the debugger is currently stopped at
+    /// (attempting or exiting it), if any.
+    pub fn current_rule(&self) -> Option<&str> {
+        match self.current_event() {
+            Some(DebuggerEvent::Breakpoint(rule, ..) | DebuggerEvent::RuleExit(rule, ..)) => {
+                Some(rule)
+            }
+            _ => None,
+        }
+    }
+
+    /// Describes where the debugger is stopped for quoting it,
+    /// e.g. "stopped at `alpha` @ line 3 col 5".
+    pub fn position_summary(&self) -> Option<String> {
+        let input = self.run_input();
+        let describe = |rule: &str, pos: usize, what: &str| {
+            let (line, col) = position::line_col(input, pos);
+            format!("{} `{}` @ line {} col {}", what, rule, line, col)
+        };
+        match self.current_event()? {
+            DebuggerEvent::Breakpoint(rule, pos, ..) => Some(describe(rule, *pos, "stopped at")),
+            DebuggerEvent::RuleExit(rule, pos, true) => Some(describe(rule, *pos, "matched")),
+            DebuggerEvent::RuleExit(rule, pos, false) => Some(describe(rule, *pos, "failed")),
+            _ => None,
+        }
+    }
+
+    /// Returns the events of the run (followed by its error, if it failed)
+    /// as a text log, one numbered event per line prefixed with its input `line:col`.
+    pub fn trace_log(&self) -> String {
+        let input = self.run_input();
+        let failure = match (&self.error, self.events.back()) {
+            (Some(error), Some(last)) if *last != DebuggerEvent::Eof => {
+                Some(DebuggerEvent::Error(error.clone(), self.error_pos))
+            }
+            _ => None,
+        };
+        self.events
+            .iter()
+            .chain(failure.as_ref())
+            .enumerate()
+            .map(|(index, event)| {
+                let pos = match event {
+                    DebuggerEvent::Breakpoint(_, pos, ..)
+                    | DebuggerEvent::RuleExit(_, pos, _)
+                    | DebuggerEvent::Error(_, Some(pos)) => Some(*pos),
+                    _ => None,
+                };
+                match pos {
+                    Some(pos) => {
+                        let (line, col) = position::line_col(input, pos);
+                        format!("{}. {}:{} {}\n", index + 1, line, col, event)
+                    }
+                    None => format!("{}. {}\n", index + 1, event),
+                }
+            })
+            .collect()
+    }
+
+    /// Returns the (1-based) number of the current event
+    /// and the total number of events to step through, excluding the final `Eof`.
+    pub fn progress(&self) -> (usize, usize) {
+        let total = self
+            .events
+            .iter()
+            .filter(|event| **event != DebuggerEvent::Eof)
+            .count();
+        ((self.cursor + 1).min(total), total)
+    }
+
+    /// Describes the current event for screen readers,
+    /// as the highlighted input only conveys it by color.
+    pub fn announcement(&self) -> String {
+        let t = self.lang.messages();
+        match self.current_event() {
+            Some(DebuggerEvent::Breakpoint(rule, pos, ..)) => (t.announce_breakpoint)(rule, *pos),
+            Some(DebuggerEvent::RuleExit(rule, pos, true)) => (t.announce_matched)(rule, *pos),
+            Some(DebuggerEvent::RuleExit(rule, pos, false)) => (t.announce_failed)(rule, *pos),
+            _ => String::new(),
+        }
+    }
+
+    /// Returns the call stack depth of each event:
+    /// the depth of a breakpoint is the length of its call stack,
+    /// and a rule exit has the depth of the breakpoint it exits.
+    /// Other events have the depth 0.
+    pub fn event_depths(&self) -> Vec<usize> {
+        let mut open = vec![];
+        self.events
+            .iter()
+            .map(|event| match event {
+                DebuggerEvent::Breakpoint(.., stack) => {
+                    open.push(stack.len());
+                    stack.len()
+                }
+                DebuggerEvent::RuleExit(..) => open.pop().unwrap_or(0),
+                _ => 0,
+            })
+            .collect()
+    }
+
+    /// Returns the input span that the rule failing at the current event
+    /// consumed before it backtracked: from where the rule was attempted
+    /// to the furthest position reached by the (breakpoint) rules it called.
+    /// It returns `None` if the current event isn't a rule failure
+    /// or the rule failed without advancing.
+    pub fn backtracked_span(&self) -> Option<(usize, usize)> {
+        let Some(DebuggerEvent::RuleExit(_, start, false)) = self.current_event() else {
+            return None;
+        };
+        let depths = self.event_depths();
+        let depth = depths[self.cursor];
+        // the breakpoint this event exits
+        let entry = (0..self.cursor).rev().find(|index| {
+            depths[*index] == depth && matches!(self.events[*index], DebuggerEvent::Breakpoint(..))
+        })?;
+        let furthest =
+            self.events
+                .range(entry + 1..self.cursor)
+                .filter_map(|event| match event {
+                    DebuggerEvent::Breakpoint(_, pos, ..)
+                    | DebuggerEvent::RuleExit(_, pos, true) => Some(*pos),
+                    _ => None,
+                })
+                .max()?;
+        (furthest > *start).then_some((*start, furthest))
+    }
+
+    /// Corrects a position of a streamed event (see `DebuggerEvent::Correction`).
+    pub fn correct(&mut self, index: usize, pos: usize) {
+        match self.events.get_mut(index) {
+            Some(DebuggerEvent::Breakpoint(_, _, end, _)) => *end = Some(pos),
+            Some(DebuggerEvent::RuleExit(_, exit, _)) => *exit = pos,
+            _ => {}
+        }
+    }
+
+    /// Recounts the rule hits of the events up to the cursor.
+    fn count_hits(&mut self) {
+        self.hits.clear();
+        for event in self.events.iter().take(self.cursor + 1) {
+            if let DebuggerEvent::Breakpoint(rule, ..) = event {
+                *self.hits.entry(rule.clone()).or_default() += 1;
+            }
+        }
+    }
+}
+
+/// The main web component.
+pub struct App {
+    /// the grammar textarea
+    grammar_ref: NodeRef,
+    /// the input textarea
+    input_ref: NodeRef,
+    /// the error modal dialog
+    modal_ref: NodeRef,
+    /// the select of the rule to run
+    rule_run_ref: NodeRef,
+    /// whether the error modal dialog should be shown after rendering
+    show_error: bool,
+    /// the dialog confirming a "Clear"
+    confirm_ref: NodeRef,
+    /// whether the confirm dialog should be shown after rendering
+    show_confirm: bool,
+    /// the element that had the focus before the error dialog was shown,
+    /// so that it gets it back once the dialog is closed
+    error_opener: Option<HtmlElement>,
+    /// the keyboard shortcuts listener (removed when dropped)
+    _keydown: EventListener,
+    /// the listener of the commands posted by the host page (removed when dropped)
+    _host_commands: EventListener,
+    /// the pending sending of the modified grammar to the worker
+    grammar_debounce: Option<Timeout>,
+    /// the pending sending of the modified input to the worker
+    input_debounce: Option<Timeout>,
+    /// the interval that continues the debugger session during playback
+    /// (cancelled when dropped)
+    playback: Option<Interval>,
+    /// the timeout that hides the success message
+    /// (cancelled when dropped)
+    success_timeout: Option<Timeout>,
+    /// the highlighted grammar behind its textarea
+    grammar_highlight_ref: NodeRef,
+    /// the grammar shown instead of its textarea while running
+    grammar_view_ref: NodeRef,
+    /// the highlighted definition of the current rule in the grammar view
+    active_rule_ref: NodeRef,
+    /// the rule the grammar view was last scrolled to
+    scrolled_rule: Option<String>,
+    /// the grammar and input panes
+    panes_ref: NodeRef,
+    /// whether the divider between the panes is being dragged
+    split_dragging: bool,
+    /// the maximize button of the grammar pane
+    grammar_maximize_ref: NodeRef,
+    /// the maximize button of the input pane
+    input_maximize_ref: NodeRef,
+    /// the pane a file is dragged over, if any
+    drop_target: Option<Pane>,
+    /// the element to focus after rendering, if any
+    /// (e.g. the textarea of a maximized pane)
+    focus_after_render: Option<NodeRef>,
+    /// for the communication with the debugger worker
+    worker: WorkerBridge,
+    /// the state of the web debugger
+    state: AppState,
+}
+
+/// The bridge to the debugger worker.
+struct WorkerBridge {
+    bridge: Box<dyn Bridge<Worker>>,
+    /// the epoch of the debugger context in the worker
+    /// (see `WorkerInput::bumps_epoch`) once it has handled the sent messages
+    epoch: usize,
+}
+
+impl WorkerBridge {
+    /// Sends a message to the worker, keeping track of its epoch.
+    fn send(&mut self, msg: WorkerInput) {
+        if msg.bumps_epoch() {
+            self.epoch += 1;
+        }
+        self.bridge.send(msg);
+    }
+}
+
+/// The possible UI messages.
+pub enum Message {
+    /// the grammar textarea was modified
+    GrammarChange,
+    /// a key was pressed in the grammar textarea
+    GrammarKeyDown(KeyboardEvent),
+    /// an indentation was selected for the grammar editor
+    SelectIndent(Event),
+    /// the grammar textarea hasn't been modified for a while
+    FlushGrammar,
+    /// the grammar textarea was scrolled
+    GrammarScroll,
+    /// the input textarea was modified
+    InputChange,
+    /// the caret or selection of the input textarea may have moved
+    InputCaret,
+    /// the input textarea hasn't been modified for a while
+    FlushInput,
+    /// the "Run" button was clicked
+    Run,
+    /// the "Run to end" button was clicked
+    RunToEnd,
+    /// the "Run selection" button was clicked
+    RunSelection,
+    /// the "Run to cursor" button was clicked
+    RunToCursor,
+    /// a rule was selected in the "Run to rule" dropdown
+    SelectTargetRule(Event),
+    /// the "Run to rule" button was clicked
+    RunToRule,
+    /// the "Run all" button was clicked
+    RunAll,
+    /// the "Continue" button was clicked
+    Continue,
+    /// the "Back" button was clicked
+    Previous,
+    /// the "Step over" button was clicked
+    StepOver,
+    /// an event was clicked in the timeline
+    /// the form is: (event_index)
+    JumpTo(usize),
+    /// a column header of the rule statistics was clicked
+    SortStats(StatsOrder),
+    /// the "Stop" button was clicked
+    Stop,
+    /// the "Reset" button was clicked
+    Reset,
+    /// the "Play"/"Pause" button was clicked
+    TogglePlay,
+    /// the playback speed slider was moved
+    SetPlaybackSpeed(InputEvent),
+    /// the "Add all breakpoint" button was clicked
+    AddAllBreakpoints,
+    /// the "Remove all breakpoint" button was clicked
+    RemoveAllBreakpoints,
+    /// the "Download tree" button was clicked
+    DownloadTree,
+    /// the "Export trace" button was clicked
+    ExportTrace,
+    /// the "Copy share link" button was clicked
+    CopyShareLink,
+    /// the share link was written to the clipboard,
+    /// or else it has to be copied by hand
+    ShareLinkCopied(Option<String>),
+    /// the "Get embed code" button was clicked
+    GetEmbedCode,
+    /// the width (or else height) of the embedded iframe was changed
+    EmbedSizeChange(bool, InputEvent),
+    /// the embed code was written to the clipboard, or not
+    EmbedCopied(bool),
+    /// the theme button was clicked
+    ToggleTheme,
+    /// a language was selected
+    SelectLang(Event),
+    /// the maximize button of a pane was clicked
+    ToggleMaximized(Pane),
+    /// a file is dragged over a pane
+    DragOver(Pane, DragEvent),
+    /// the dragged file left the pane
+    DragLeave,
+    /// a file was dropped on a pane
+    DropFile(Pane, DragEvent),
+    /// Esc was pressed in a form field
+    RestorePane,
+    /// Esc was pressed elsewhere
+    Escape,
+    /// the divider between the grammar and input panes was grabbed
+    SplitDragStart(PointerEvent),
+    /// the grabbed divider was moved
+    SplitDrag(PointerEvent),
+    /// the grabbed divider was released
+    SplitDragEnd,
+    /// the "Format" button was clicked
+    FormatGrammar,
+    /// a rule was clicked in the rule graph
+    SelectRule(String),
+    /// a warning was dismissed
+    /// the form is: (warning_index)
+    DismissWarning(usize),
+    /// the success message was dismissed (or timed out)
+    DismissSuccess,
+    /// the error dialog was closed
+    ErrorDialogClosed,
+    /// a "Clear" button was clicked
+    AskClear(ClearTarget),
+    /// the "Clear" button of the confirm dialog was clicked
+    ConfirmClear,
+    /// the confirm dialog was closed
+    ConfirmDialogClosed,
+    /// a rule name was clicked to show its definition in the grammar
+    JumpToRule(String),
+    /// a grammar error of the error dialog was clicked to show its location
+    JumpToGrammarError(usize),
+    /// the "Export session" button was clicked
+    ExportSession,
+    /// a session file was picked to be imported
+    ImportSession(Event),
+    /// a session file was read
+    SessionLoaded(Result<String, String>),
+    /// the name of the session to save, load or delete was modified or picked
+    SessionNameChange(Event),
+    /// the "Save" button of the saved sessions was clicked
+    SaveSession,
+    /// the "Load" button of the saved sessions was clicked
+    LoadSavedSession,
+    /// the rule name to run instead of the selected one was typed
+    TypedRuleChange(InputEvent),
+    /// the "Copy" button of the current position was clicked
+    CopyPosition,
+    /// the current position was written to the clipboard,
+    /// or else it has to be copied by hand
+    PositionCopied(Option<String>),
+    /// the "Restore" button of the draft was clicked
+    RestoreDraft,
+    /// the "Discard" button of the draft was clicked
+    DiscardDraft,
+    /// the "Delete" button of the saved sessions was clicked
+    DeleteSavedSession,
+    /// the saved sessions were listed
+    SavedSessionsListed(Result<Vec<String>, String>),
+    /// a session was saved or deleted
+    SavedSessionsChanged(Result<(), String>),
+    /// a saved session was read
+    SavedSessionLoaded(Result<Session, String>),
+    /// a grammar file was picked to be loaded
+    UploadGrammar(Event),
+    /// a grammar file was read (or fetched)
+    GrammarLoaded(Result<String, String>),
+    /// the grammar URL was modified
+    GrammarUrlChange(InputEvent),
+    /// the "Fetch grammar" button was clicked
+    FetchGrammar,
+    /// an input tab was clicked
+    SelectInput(usize),
+    /// the "+" input tab was clicked
+    AddInput,
+    /// the "×" button of an input tab was clicked
+    CloseInput(usize),
+    /// an input file was picked to be loaded
+    UploadInput(Event),
+    /// an input file was read
+    /// the form is: (file_name, result)
+    InputLoaded(String, Result<String, String>),
+    /// the selection of the rule to run was changed
+    SelectRuleToRun(Event),
+    /// the breakpoint was ticked or unticked
+    ChangeBreakpoint(Event),
+    /// the rules order button was clicked
+    ToggleSortMode,
+    /// the "skip silent rules" checkbox was ticked or unticked
+    ToggleSkipSilent,
+    /// the "optimized" checkbox of the AST was ticked or unticked
+    ToggleAstOptimized,
+    /// the "show whitespace" checkbox was ticked or unticked
+    ToggleWhitespace,
+    /// the "normalize line endings" checkbox was ticked or unticked
+    ToggleNormalizeLineEndings,
+    /// the breakpoints filter was modified
+    FilterBreakpoints(InputEvent),
+    /// the list of events was scrolled
+    ScrollEvents(Event),
+    /// the position window of a breakpoint was modified
+    /// the form is: (rule_name, is_window_end, event)
+    ChangeBreakpointWindow(String, bool, Event),
+    /// the number of hits to ignore of a breakpoint was modified
+    /// the form is: (rule_name, event)
+    ChangeBreakpointIgnore(String, Event),
+    /// the host page posted a command
+    Host(HostCommand),
+    /// the worker sent a message
+    /// the form is: (epoch, event)
+    WorkerMsg(usize, DebuggerEvent),
+}
+
+impl App {
+    /// Moves the debugger to the event at `index` (see `AppState::step_to`)
+    /// and shows the success message if the session ended after a successful parse.
+    fn step_to(&mut self, ctx: &Context<Self>, index: usize) {
+        if let Some(success) = self.state.step_to(index) {
+            self.playback = None;
+            self.worker.send(WorkerInput::EndSession);
+            if success {
+                let _ = HostEvent::Eof.post();
+                self.state.success = true;
+                let link = ctx.link().clone();
+                self.success_timeout = Some(Timeout::new(SUCCESS_MS, move || {
+                    link.send_message(Message::DismissSuccess)
+                }));
+            }
+        } else {
+            self.post_breakpoint();
+        }
+    }
+
+    /// Ends a "Run to end" session once its last event is received,
+    /// as if all its events were continued through.
+    /// (It's also how a "Run to rule" session ends if the rule isn't hit.)
+    fn finish_run_to_end(&mut self, ctx: &Context<Self>) {
+        let run_to_end = std::mem::take(&mut self.state.run_to_end);
+        let run_to_rule = self.state.run_to_rule.take().is_some();
+        if !(run_to_end || run_to_rule) || !self.state.running {
+            return;
+        }
+        if self.state.events.is_empty() {
+            // the parsing failed before any breakpoint was hit
+            self.worker.send(WorkerInput::EndSession);
+            self.state.running = false;
+        } else {
+            self.step_to(ctx, self.state.events.len());
+        }
+    }
+
+    /// Focuses the grammar textarea and selects the `start..end` byte range of it.
+    fn select_grammar(&self, start: usize, end: usize) {
+        let grammar = &self.state.grammar;
+        if let Some(textarea) = self.grammar_ref.cast::<HtmlTextAreaElement>() {
+            let _ = textarea.focus();
+            let _ = textarea.set_selection_range(
+                position::utf16_offset(grammar, start) as u32,
+                position::utf16_offset(grammar, end) as u32,
+            );
+            // browsers don't always scroll to the selection
+            let (line, _) = position::line_col(grammar, start);
+            let lines = grammar.lines().count().max(1);
+            textarea.set_scroll_top(textarea.scroll_height() * (line as i32 - 1) / lines as i32);
+        } else if let Some(view) = self.grammar_view_ref.cast::<HtmlElement>() {
+            // while running, the grammar can't be selected, only scrolled to
+            let (line, _) = position::line_col(grammar, start);
+            let lines = grammar.lines().count().max(1);
+            view.set_scroll_top(view.scroll_height() * (line as i32 - 1) / lines as i32);
+        }
+    }
+
+    /// Posts the error (if any) to the host page.
+    fn post_error(&self) {
+        if let Some(message) = &self.state.error {
+            let event = HostEvent::Error {
+                message: message.clone(),
+            };
+            let _ = event.post();
+        }
+    }
+
+    /// Posts the current breakpoint (if any) to the host page.
+    fn post_breakpoint(&self) {
+        if let Some(DebuggerEvent::Breakpoint(rule, pos, ..)) = self.state.current_event() {
+            let event = HostEvent::Breakpoint {
+                rule: rule.clone(),
+                pos: *pos,
+            };
+            let _ = event.post();
+        }
+    }
+
+    /// Sends the modified grammar to the worker if it's pending.
+    fn flush_grammar(&mut self) {
+        if self.grammar_debounce.take().is_some() {
+            self.worker
+                .send(WorkerInput::LoadGrammar(self.state.grammar.clone()));
+            let _ = self.state.shared().update_location();
+            self.save_draft();
+        }
+    }
+
+    /// Sends the modified input to the worker if it's pending.
+    fn flush_input(&mut self) {
+        if self.input_debounce.take().is_some() {
+            self.worker
+                .send(WorkerInput::LoadInput(self.state.input().to_owned()));
+            let _ = self.state.shared().update_location();
+            self.save_draft();
+        }
+    }
+
+    /// Saves the edited session as the draft to recover after a crash or reload.
+    fn save_draft(&self) {
+        let _ = LocalStorage::set(DRAFT_KEY, self.state.session());
+    }
+
+    /// Starts (or restarts) continuing the debugger session
+    /// at the current playback speed.
+    fn start_playback(&mut self, ctx: &Context<Self>) {
+        let link = ctx.link().clone();
+        self.playback = Some(Interval::new(self.state.playback_speed, move || {
+            link.send_message(Message::Continue)
+        }));
+    }
+
+    /// Makes the input tab at `index` active and sends its content to the worker.
+    fn select_input(&mut self, index: usize) {
+        // the pending edits are sent before switching
+        self.flush_input();
+        self.state.active_input = index;
+        self.state.input_caret = None;
+        self.state.input_file = None;
+        self.state.error_pos = None;
+        self.state.run_selection = None;
+        self.state.partial_parse = None;
+        if let Some(input) = self.input_ref.cast::<HtmlTextAreaElement>() {
+            input.set_value(self.state.input());
+        }
+        self.worker
+            .send(WorkerInput::LoadInput(self.state.input().to_owned()));
+        let _ = self.state.shared().update_location();
+    }
+
+    /// Starts a debugger session on the whole active input
+    /// or only on the `selection` byte range of it.
+    /// It's a no-op while a session is running: it has to be stopped
+    /// (or stepped through to its end) first.
+    fn start_run(&mut self, selection: Option<(usize, usize)>) {
+        self.start_session(selection, None);
+    }
+
+    /// Like `start_run` on the whole active input, but the session only stops
+    /// at the breakpoints hit at or after the `pos` byte offset.
+    fn start_run_to_position(&mut self, pos: usize) {
+        self.start_session(None, Some(pos));
+    }
+
+    fn start_session(&mut self, selection: Option<(usize, usize)>, from_pos: Option<usize>) {
+        if self.state.running || !self.state.has_rules() {
+            return;
+        }
+        self.flush_grammar();
+        self.flush_input();
+        if self.state.error.is_none() {
+            self.state.running = true;
+            self.state.events.clear();
+            self.state.cursor = 0;
+            self.state.events_scroll = 0;
+            self.state.hits.clear();
+            self.state.parse_tree = None;
+            self.state.truncated = None;
+            self.state.attempts.clear();
+            self.state.parse_time = None;
+            self.state.expected = None;
+            self.state.copy_fallback = None;
+            self.state.run_selection = selection;
+            self.state.partial_parse = None;
+            self.state.success = false;
+            self.success_timeout = None;
+            let rule = self.state.rule_to_run().to_owned();
+            match (selection, from_pos) {
+                (Some(_), _) => self
+                    .worker
+                    .send(WorkerInput::RunOn(rule, self.state.run_input().to_owned())),
+                (None, Some(pos)) => self.worker.send(WorkerInput::RunToPosition(rule, pos)),
+                (None, None) => self.worker.send(WorkerInput::Run(rule)),
+            }
+        } else {
+            self.show_error_dialog();
+        }
+    }
+
+    /// Runs the rule on all the input tabs (without a debugger session).
+    /// It's a no-op while a session is running.
+    fn start_batch(&mut self) {
+        if self.state.running || !self.state.has_rules() {
+            return;
+        }
+        self.flush_grammar();
+        self.flush_input();
+        if self.state.error.is_none() {
+            self.state.batch_results.clear();
+            let inputs = self
+                .state
+                .inputs
+                .iter()
+                .map(|(_, input)| input.clone())
+                .collect();
+            self.worker.send(WorkerInput::RunBatch(
+                self.state.rule_to_run().to_owned(),
+                inputs,
+            ));
+        } else {
+            self.show_error_dialog();
+        }
+    }
+
+    /// Shows the error dialog (as a modal, so the focus stays in it).
+    fn show_error_dialog(&mut self) {
+        if let Some(dialog) = self.modal_ref.cast::<HtmlDialogElement>() {
+            self.error_opener = gloo_utils::document()
+                .active_element()
+                .and_then(|element| element.dyn_into::<HtmlElement>().ok());
+            let _ = dialog.show_modal();
+        }
+    }
+
+    /// Empties the grammar or input textarea and sends it to the worker.
+    fn clear(&mut self, target: ClearTarget) {
+        match target {
+            ClearTarget::Grammar => {
+                self.state.grammar.clear();
+                // the rules (and so the breakpoints) are updated once the worker responds
+                self.grammar_debounce = None;
+                self.worker.send(WorkerInput::LoadGrammar(String::new()));
+            }
+            ClearTarget::Input => {
+                self.state.set_input(String::new());
+                self.state.input_file = None;
+                self.state.input_caret = None;
+                self.state.error_pos = None;
+                self.state.run_selection = None;
+                self.state.partial_parse = None;
+                self.input_debounce = None;
+                self.worker.send(WorkerInput::LoadInput(String::new()));
+            }
+        }
+        let _ = self.state.shared().update_location();
+        self.save_draft();
+    }
+
+    /// Sets the error message and shows it in the error dialog.
+    fn report_error(&mut self, error: String) {
+        self.state.error = Some(error);
+        self.show_error = true;
+    }
+
+    /// Shows a warning (unless it's already shown).
+    fn report_warning(&mut self, warning: String) {
+        if !self.state.warnings.contains(&warning) {
+            self.state.warnings.push(warning);
+        }
+    }
+
+    /// Replaces the worker's breakpoints with the enabled ones in the state.
+    fn sync_breakpoints(&mut self) {
+        self.worker.send(WorkerInput::DeleteAllBreakpoints);
+        for breakpoint in self.state.breakpoints.iter() {
+            self.worker.send(WorkerInput::SetBreakpointIgnore(
+                breakpoint.rule.clone(),
+                breakpoint.ignore,
+            ));
+            if breakpoint.enabled && !self.state.is_skipped(&breakpoint.rule) {
+                self.worker.send(breakpoint.worker_input());
+            }
+        }
+    }
+
+    /// Replaces the current state with an imported session
+    /// and sends it to the worker.
+    fn load_session(&mut self, session: Session) {
+        self.state.grammar = session.grammar;
+        if session.inputs.is_empty() {
+            // sessions exported before the input tabs only have one input
+            let name = (self.state.lang.messages().input_name)(1);
+            self.state.inputs = vec![(name, session.input)];
+            self.state.active_input = 0;
+        } else {
+            self.state.active_input = session.active_input.min(session.inputs.len() - 1);
+            self.state.inputs = session.inputs;
+        }
+        if self.state.normalize_line_endings {
+            self.state.normalize_inputs();
+        }
+        self.state.input_file = None;
+        self.state.run_selection = None;
+        self.state.partial_parse = None;
+        self.state.breakpoints = session.breakpoints;
+        self.sync_breakpoints();
+        self.state.to_run = session.to_run;
+        self.worker
+            .send(WorkerInput::LoadGrammar(self.state.grammar.clone()));
+        self.worker
+            .send(WorkerInput::LoadInput(self.state.input().to_owned()));
+        let _ = self.state.shared().update_location();
+    }
+
+    fn input_file_note(&self) -> Html {
+        let t = self.state.lang.messages();
+        match &self.state.input_file {
+            Some(name) if self.state.input().len() > LARGE_INPUT => html! {
+                <p class="nes-text is-warning">{(t.large_input_loaded)(name, self.state.input().len())}</p>
+            },
+            Some(name) => html! {
+                <p class="nes-text">{(t.input_loaded)(name, self.state.input().len())}</p>
+            },
+            None => html! {},
+        }
+    }
+
+    fn grammar_error_marker(&self) -> Html {
+        let t = self.state.lang.messages();
+        match self.state.grammar_errors.first() {
+            Some(error) => {
+                let grammar = &self.state.grammar;
+                let pos = position::byte_offset(grammar, error.line, error.col);
+                let (before, current, after) = position::split_line_at_char(grammar, pos);
+                let current = match current {
+                    "" | "\r" | "\n" => "␃",
+                    _ => current,
+                };
+                html! {
+                    <div class="error-location" title={error.message.clone()}>
+                        <p class="nes-text is-error">{(t.grammar_error_at)(error.line, error.col)}</p>
+                        <pre>{before}<span class="nes-text is-error is-dark grammar-error">{current}</span>{after}</pre>
+                    </div>
+                }
+            }
+            None => html! {},
+        }
+    }
+
+    fn error_location(&self) -> Html {
+        let t = self.state.lang.messages();
+        match self.state.error_pos {
+            Some(pos) => {
+                let input = self.state.run_input();
+                let (line, col) = position::line_col(input, pos);
+                let (before, current, after) = position::split_line_at_char(input, pos);
+                let current = match current {
+                    "" => "␃",
+                    "\r" => "␍",
+                    "\n" => "␊",
+                    _ => current,
+                };
+                html! {
+                    <div class="error-location">
+                        <p class="nes-text is-error">{(t.parsing_failed_at)(line, col)}</p>
+                        <pre>{before}<span class="nes-text is-error is-dark">{current}</span>{after}</pre>
+                    </div>
+                }
+            }
+            None => html! {},
+        }
+    }
+
+    fn partial_parse_location(&self) -> Html {
+        let t = self.state.lang.messages();
+        match self.state.partial_parse {
+            Some(pos) => {
+                let input = self.state.run_input();
+                let (line, col) = position::line_col(input, pos);
+                let (before, current, after) = position::split_line_at_char(input, pos);
+                html! {
+                    <div class="error-location">
+                        <p class="nes-text is-warning">
+                            {(t.partial_parse)(line, col, input.len().saturating_sub(pos))}
+                        </p>
+                        <pre>{before}<span class="nes-text is-warning is-dark unconsumed">{current}{after}</span></pre>
+                    </div>
+                }
+            }
+            None => html! {},
+        }
+    }
+
+    fn input_tabs(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let closable = self.state.inputs.len() > 1;
+        html! {
+            <div class="input-tabs">
+            {self.state.inputs.iter().enumerate().map(|(index, (name, _))| {
+                let class = if index == self.state.active_input {
+                    "nes-btn is-primary input-tab"
+                } else {
+                    "nes-btn input-tab"
+                };
+                html! {
+                    <span class="input-tab-group">
+                        <button type="button" class={class} onclick={ctx.link().callback(move |_| Message::SelectInput(index))}>{name}</button>
+                        if closable {
+                            <button type="button" class="nes-btn is-error input-tab-close" title={format!("{} {}", t.close_input, name)}
+                                onclick={ctx.link().callback(move |_| Message::CloseInput(index))}>{"×"}</button>
+                        }
+                    </span>
+                }
+            }).collect::<Html>()}
+            <button type="button" class="nes-btn input-tab" title={t.new_input} onclick={ctx.link().callback(|_| Message::AddInput)}>{"+"}</button>
+            </div>
+        }
+    }
+
+    fn whitespace_toggle(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        html! {
+            <label class="show-whitespace">
+                <input type="checkbox" class="nes-checkbox" checked={self.state.show_whitespace}
+                    onchange={ctx.link().callback(|_| Message::ToggleWhitespace)} />
+                <span>{t.show_whitespace}</span>
+            </label>
+        }
+    }
+
+    /// Shows the whitespace of the text as glyphs if the "show whitespace" mode is on.
+    fn visible_text(&self, text: &str) -> String {
+        if self.state.show_whitespace {
+            position::show_whitespace(text)
+        } else {
+            text.to_owned()
+        }
+    }
+
+    fn input_display(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        if !self.state.running {
+            // a textarea can't show the glyphs, so they're shown in a preview below it
+            let preview = if self.state.show_whitespace {
+                html! {
+                    <pre class="whitespace-preview" aria-hidden="true">
+                        {position::show_whitespace(self.state.input())}
+                    </pre>
+                }
+            } else {
+                html! {}
+            };
+            html! {
+                <div class={self.pane_class(Pane::Input)} style={self.pane_style(false)}
+                    ondragover={ctx.link().callback(|e| Message::DragOver(Pane::Input, e))}
+                    ondragleave={ctx.link().callback(|_| Message::DragLeave)}
+                    ondrop={ctx.link().callback(|e| Message::DropFile(Pane::Input, e))}>
+                    {self.maximize_button(ctx, Pane::Input)}
+                    <label for="parser-input">{t.input_to_parse}</label>
+                    {self.input_tabs(ctx)}
+                    <input type="file" class="input-upload" onchange={ctx.link().callback(Message::UploadInput)} />
+                    {self.whitespace_toggle(ctx)}
+                    <label class="normalize-line-endings">
+                        <input type="checkbox" class="nes-checkbox" checked={self.state.normalize_line_endings}
+                            onchange={ctx.link().callback(|_| Message::ToggleNormalizeLineEndings)} />
+                        <span>{t.normalize_line_endings}</span>
+                    </label>
+                    if self.state.input().is_empty() {
+                        <button type="button" class="nes-btn is-disabled clear-button">{t.clear}</button>
+                    } else {
+                        <button type="button" class="nes-btn is-error clear-button" onclick={ctx.link().callback(|_| Message::AskClear(ClearTarget::Input))}>{t.clear}</button>
+                    }
+                    <textarea id="parser-input"  name="parser-input" class="parser-input nes-textarea" rows="20" cols="33"
+                    ref={self.input_ref.clone()} value={self.state.input().to_owned()} oninput={ctx.link().callback(|_| Message::InputChange)}
+                    onkeyup={ctx.link().callback(|_| Message::InputCaret)} onclick={ctx.link().callback(|_| Message::InputCaret)}
+                    onselect={ctx.link().callback(|_| Message::InputCaret)}> </textarea>
+                    <p class="input-status" aria-live="polite">{position::input_status(self.state.input(), self.state.input_caret)}</p>
+                    {preview}
+                    {self.input_file_note()}
+                    {self.error_location()}
+                    {self.partial_parse_location()}
+                </div>
+            }
+        } else {
+            let input = self.state.run_input();
+            // pest reports byte offsets
+            let highlight = match self.state.current_event() {
+                Some(DebuggerEvent::Breakpoint(_, start_idx, end_idx, _)) => {
+                    let (line, col) = position::line_col(input, *start_idx);
+                    let parts = match end_idx {
+                        Some(end_idx) => position::split_at_span(input, *start_idx, *end_idx),
+                        None => position::split_at_char(input, *start_idx),
+                    };
+                    Some((
+                        (t.breakpoint_at)(line, col),
+                        parts,
+                        "nes-text is-primary is-dark",
+                    ))
+                }
+                Some(DebuggerEvent::RuleExit(rule, pos, matched)) => {
+                    let (line, col) = position::line_col(input, *pos);
+                    if *matched {
+                        Some((
+                            (t.rule_matched_until)(rule, line, col),
+                            position::split_at_char(input, *pos),
+                            "nes-text is-success is-dark",
+                        ))
+                    } else if let Some((start, end)) = self.state.backtracked_span() {
+                        let (end_line, end_col) = position::line_col(input, end);
+                        Some((
+                            (t.rule_backtracked)(rule, line, col, end_line, end_col),
+                            position::split_at_span(input, start, end),
+                            "nes-text is-error backtracked",
+                        ))
+                    } else {
+                        Some((
+                            (t.rule_failed_at)(rule, line, col),
+                            position::split_at_char(input, *pos),
+                            "nes-text is-error is-dark",
+                        ))
+                    }
+                }
+                _ => None,
+            };
+            if let Some((status, (start, rest_1, rest_2), class)) = highlight {
+                let rest_1 = if self.state.show_whitespace {
+                    position::show_whitespace(rest_1)
+                } else {
+                    rest_1
+                        .replace(' ', "␣")
+                        .replace('\r', "␍\r")
+                        .replace('\n', "␊\n")
+                };
+                let rest_1 = if input.is_empty() {
+                    String::from(t.empty_input)
+                } else if rest_1.is_empty() {
+                    String::from("␃")
+                } else {
+                    rest_1
+                };
+                let call_stack = match self.state.current_event() {
+                    Some(DebuggerEvent::Breakpoint(.., stack)) => html! {
+                        <p class="call-stack">{stack.join(" → ")}</p>
+                    },
+                    _ => html! {},
+                };
+                html! {
+                    <div class={self.pane_class(Pane::Input)} style={self.pane_style(false)}>
+                        {self.maximize_button(ctx, Pane::Input)}
+                        <label for="parser-input">{t.input_to_parse}</label>
+                        {call_stack}
+                        {self.whitespace_toggle(ctx)}
+                        <p class="nes-text is-primary" role="status" aria-live="polite">
+                            <span aria-hidden="true">{status}</span>
+                            <span class="visually-hidden">{self.state.announcement()}</span>
+                            <button type="button" class="nes-btn copy-position" title={t.copy_title}
+                                onclick={ctx.link().callback(|_| Message::CopyPosition)}>{t.copy}</button>
+                        </p>
+                        if let Some(text) = &self.state.copy_fallback {
+                            {Self::copy_fallback(t, text)}
+                        }
+                        <div id="parser-input"  name="parser-input" class="parser-input nes-textarea">
+                            {self.visible_text(start)} <span class={class}>{rest_1}</span> {self.visible_text(rest_2)}
+                        </div>
+                        {Self::highlight_legend(t)}
+                    </div>
+                }
+            } else {
+                html! {
+                    <div class={self.pane_class(Pane::Input)} style={self.pane_style(false)}>
+                        {self.maximize_button(ctx, Pane::Input)}
+                        <label for="parser-input">{t.input_to_parse}</label>
+                        {self.whitespace_toggle(ctx)}
+                        <div id="parser-input"  name="parser-input" class="parser-input nes-textarea">
+                            if self.state.run_input().is_empty() {
+                                <span class="nes-text is-disabled">{t.empty_input}</span>
+                            } else {
+                                {self.visible_text(self.state.run_input())}
+                            }
+                        </div>
+                    </div>
+                }
+            }
+        }
+    }
+
+    /// The select of the indentation the Tab key inserts in the grammar.
+    fn indent_select(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let options = Indent::ALL
+            .into_iter()
+            .map(|indent| {
+                let name = match indent {
+                    Indent::Spaces(width) => format!("{} {}", width, t.indent_spaces),
+                    Indent::Tab => t.indent_tab.to_owned(),
+                };
+                html! {
+                    <option value={indent.code()} selected={indent == self.state.indent}>{name}</option>
+                }
+            })
+            .collect::<Html>();
+        html! {
+            <div class="nes-select indent-select">
+                <select aria-label={t.indent} title={t.indent} disabled={self.state.running}
+                    onchange={ctx.link().callback(Message::SelectIndent)}>
+                    {options}
+                </select>
+            </div>
+        }
+    }
+
+    /// The grammar textarea, or while running, the grammar
+    /// with the definition of the current rule highlighted.
+    fn grammar_display(&self, ctx: &Context<Self>) -> Html {
+        if !self.state.running {
+            // the highlighted grammar is shown through the transparent textarea
+            let tokens = highlight::highlight(&self.state.grammar)
+                .into_iter()
+                .map(|(token, text)| html! { <span class={token.class()}>{text}</span> })
+                .collect::<Html>();
+            return html! {
+                <div class="grammar-editor">
+                    <pre class="grammar-highlight nes-textarea" aria-hidden="true" ref={self.grammar_highlight_ref.clone()}>
+                        {tokens}
+                        // a trailing newline would otherwise not add a line
+                        {"\n"}
+                    </pre>
+                    <textarea id="grammar" class="grammar nes-textarea" rows="20" cols="33" spellcheck="false"
+                    ref={self.grammar_ref.clone()} value={self.state.grammar.clone()} oninput={ctx.link().callback(|_| Message::GrammarChange)}
+                    onkeydown={ctx.link().callback(Message::GrammarKeyDown)}
+                    onscroll={ctx.link().callback(|_| Message::GrammarScroll)}>
+                    </textarea>
+                </div>
+            };
+        }
+        // a textarea can't highlight a part of its text
+        let grammar = &self.state.grammar;
+        let definition = self
+            .state
+            .current_rule()
+            .and_then(|rule| position::rule_definition(grammar, rule));
+        html! {
+            <pre id="grammar" class="grammar grammar-view nes-textarea" ref={self.grammar_view_ref.clone()}>
+                {match definition {
+                    Some((start, _)) => {
+                        let (start, end) = position::line_span(grammar, start);
+                        html! {
+                            <>
+                            {&grammar[..start]}
+                            <mark class="active-rule" ref={self.active_rule_ref.clone()}>{&grammar[start..end]}</mark>
+                            {&grammar[end..]}
+                            </>
+                        }
+                    }
+                    None => html! { {grammar} },
+                }}
+            </pre>
+        }
+    }
+
+    /// Shows a text that couldn't be written to the clipboard, to copy it by hand.
+    fn copy_fallback(t: &Messages, text: &str) -> Html {
+        html! {
+            <label class="copy-fallback">
+                <span>{t.copy_fallback}</span>
+                <input type="text" class="nes-input" readonly={true} value={text.to_owned()}
+                    onfocus={Callback::from(|e: FocusEvent| {
+                        if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                            input.select();
+                        }
+                    })} />
+            </label>
+        }
+    }
+
+    /// Explains the colors of the highlighted input.
+    fn highlight_legend(t: &Messages) -> Html {
+        html! {
+            <p class="highlight-legend">
+                <span class="nes-text is-primary is-dark">{t.legend_attempted}</span>
+                <span class="nes-text is-success is-dark">{t.legend_matched}</span>
+                <span class="nes-text is-error is-dark">{t.legend_failed}</span>
+                <span class="nes-text is-error backtracked">{t.legend_backtracked}</span>
+            </p>
+        }
+    }
+
+    /// The button maximizing (or restoring) the pane.
+    fn maximize_button(&self, ctx: &Context<Self>, pane: Pane) -> Html {
+        let t = self.state.lang.messages();
+        let (maximized, button_ref) = match pane {
+            Pane::Grammar => (self.state.grammar_maximized, &self.grammar_maximize_ref),
+            Pane::Input => (self.state.input_maximized, &self.input_maximize_ref),
+        };
+        let (label, title) = if maximized {
+            ("🗗", t.restore_pane)
+        } else {
+            ("⛶", t.maximize_pane)
+        };
+        html! {
+            <button type="button" class="nes-btn maximize-button" ref={button_ref.clone()}
+                title={title} aria-label={title} aria-pressed={maximized.to_string()}
+                onclick={ctx.link().callback(move |_| Message::ToggleMaximized(pane))}>{label}</button>
+        }
+    }
+
+    /// Returns the classes of the pane (i.e. whether it's maximized
+    /// or a file is dragged over it).
+    fn pane_class(&self, pane: Pane) -> Classes {
+        let maximized = match pane {
+            Pane::Grammar => self.state.grammar_maximized,
+            Pane::Input => self.state.input_maximized,
+        };
+        classes!(
+            "half",
+            maximized.then_some("maximized"),
+            (self.drop_target == Some(pane)).then_some("drop-target")
+        )
+    }
+
+    /// Returns the style sizing the grammar pane (or the input one)
+    /// to its share of the split.
+    fn pane_style(&self, grammar: bool) -> String {
+        let ratio = if grammar {
+            self.state.split_ratio
+        } else {
+            1.0 - self.state.split_ratio
+        };
+        format!("flex-basis: {:.1}%", ratio * 100.0)
+    }
+
+    fn control_height(&self) -> usize {
+        // the progress bar is only shown while running
+        let progress = if self.state.running { 50 } else { 0 };
+        // the "Run to rule" dropdown is only shown if there are breakpoints
+        let run_to_rule = if self.state.run_to_rule_targets().is_empty() {
+            0
+        } else {
+            100
+        };
+        // including the typed rule input and the FIRST set
+        420 + progress
+            + run_to_rule
+            + (self.state.visible_breakpoints().count().saturating_sub(3) * 50)
+    }
+
+    fn controls(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let style = format!(
+            "clear:both; margin:20px;width: 62%; height:{}px",
+            self.control_height()
+        );
+        let enabled_button = "nes-btn".to_owned();
+        let disabled_button = "nes-btn is-disabled".to_owned();
+        let download_button = if self.state.parse_tree.is_some() {
+            html! {
+                <button type="button" class={enabled_button.clone()} onclick={ctx.link().callback(|_| Message::DownloadTree)}>{t.download_tree}</button>
+            }
+        } else {
+            html! {
+                <button type="button" class={disabled_button.clone()}>{t.download_tree}</button>
+            }
+        };
+        let trace_button = if self.state.events.is_empty() {
+            html! {
+                <button type="button" class={disabled_button.clone()}>{t.export_trace}</button>
+            }
+        } else {
+            html! {
+                <button type="button" class={enabled_button.clone()} title={t.export_trace_title}
+                    onclick={ctx.link().callback(|_| Message::ExportTrace)}>{t.export_trace}</button>
+            }
+        };
+        let session_buttons = html! {
+            <>
+            <button type="button" class={enabled_button.clone()} onclick={ctx.link().callback(|_| Message::ExportSession)}>{t.export_session}</button>
+            <label class={if self.state.running { disabled_button.clone() } else { enabled_button.clone() }}>
+                <span>{t.import_session}</span>
+                <input type="file" accept=".json" style="display:none" disabled={self.state.running} onchange={ctx.link().callback(Message::ImportSession)} />
+            </label>
+            </>
+        };
+        let progress = if self.state.running {
+            let (current, total) = self.state.progress();
+            let truncated = match self.state.truncated {
+                Some(hit) => {
+                    let shown = self
+                        .state
+                        .events
+                        .iter()
+                        .filter(|event| matches!(event, DebuggerEvent::Breakpoint(..)))
+                        .count();
+                    html! {
+                        <span class="nes-text is-warning">
+                            {(t.truncated)(shown, hit)}
+                        </span>
+                    }
+                }
+                None => html! {},
+            };
+            html! {
+                <div class="progress">
+                    <span>{(t.event_progress)(current, total)}</span>
+                    <progress class="nes-progress is-primary" value={current.to_string()} max={total.max(1).to_string()}></progress>
+                    {truncated}
+                </div>
+            }
+        } else {
+            html! {}
+        };
+        let parse_time = match self.state.parse_time {
+            Some(time) => html! {
+                <p class="parse-time">{(t.parsed_in)(time)}</p>
+            },
+            None => html! {},
+        };
+        let playback_speed = html! {
+            <label class="playback-speed">
+                {format!("{}ms", self.state.playback_speed)}
+                <input type="range" min="100" max="2000" step="100"
+                    value={self.state.playback_speed.to_string()}
+                    oninput={ctx.link().callback(Message::SetPlaybackSpeed)} />
+            </label>
+        };
+        let reset_button = html! {
+            <button type="button" class={enabled_button.clone() + " is-error"} onclick={ctx.link().callback(|_| Message::Reset)}>{t.reset}</button>
+        };
+        let share_button = html! {
+            <>
+                <button type="button" class={enabled_button.clone()} onclick={ctx.link().callback(|_| Message::CopyShareLink)}>{t.copy_share_link}</button>
+                <button type="button" class={enabled_button.clone()} title={t.get_embed_code_title} onclick={ctx.link().callback(|_| Message::GetEmbedCode)}>{t.get_embed_code}</button>
+            </>
+        };
+        // the run buttons need a rule to run
+        let run_button = if self.state.has_rules() {
+            enabled_button.clone()
+        } else {
+            disabled_button.clone()
+        };
+        let buttons = if self.state.running {
+            html! {
+                <>
+                    <button type="button" class={disabled_button.clone()} aria-label={t.run_label} aria-disabled="true">{t.run}</button>
+                    <button type="button" class={disabled_button.clone()}>{t.run_to_end}</button>
+                    <button type="button" class={disabled_button.clone()}>{t.run_selection}</button>
+                    <button type="button" class={disabled_button.clone()}>{t.run_to_cursor}</button>
+                    <button type="button" class={disabled_button.clone()}>{t.run_all}</button>
+                    <button type="button" class={enabled_button.clone() + " is-primary"} aria-label={t.continue_label} onclick={ctx.link().callback(|_| Message::Continue)}>{t.continue_run}</button>
+                    <button type="button" class={enabled_button.clone()} onclick={ctx.link().callback(|_| Message::StepOver)}>{t.step_over}</button>
+                    <button type="button" class={if self.state.cursor > 0 { enabled_button.clone() } else { disabled_button.clone() }} onclick={ctx.link().callback(|_| Message::Previous)}>{t.back}</button>
+                    <button type="button" class={enabled_button.clone() + " is-warning"} aria-label={t.stop_label} onclick={ctx.link().callback(|_| Message::Stop)}>{t.stop}</button>
+                    <button type="button" class={enabled_button.clone()} onclick={ctx.link().callback(|_| Message::TogglePlay)}>{if self.playback.is_some() { t.pause } else { t.play }}</button>
+                    {playback_speed}
+                    <button type="button" class={disabled_button.clone() + " is-success"}>{t.add_all_breakpoints}</button>
+                    <button type="button" class={disabled_button + " is-error"}>{t.remove_all_breakpoints}</button>
+                    {download_button}
+                    {trace_button.clone()}
+                    {share_button}
+                    {session_buttons}
+                    {reset_button}
+                </>
+            }
+        } else {
+            html! {
+                <>
+                    <button type="button" class={run_button.clone()} aria-label={t.run_label} onclick={ctx.link().callback(|_| Message::Run)}>{t.run}</button>
+                    <button type="button" class={run_button.clone()} title={t.run_to_end_title} onclick={ctx.link().callback(|_| Message::RunToEnd)}>{t.run_to_end}</button>
+                    <button type="button" class={run_button.clone()} title={t.run_selection_title} onclick={ctx.link().callback(|_| Message::RunSelection)}>{t.run_selection}</button>
+                    <button type="button" class={run_button.clone()} title={t.run_to_cursor_title} onclick={ctx.link().callback(|_| Message::RunToCursor)}>{t.run_to_cursor}</button>
+                    <button type="button" class={run_button.clone()} title={t.run_all_title} onclick={ctx.link().callback(|_| Message::RunAll)}>{t.run_all}</button>
+                    <button type="button" class={disabled_button.clone() + " is-primary"} aria-label={t.continue_label} aria-disabled="true">{t.continue_run}</button>
+                    <button type="button" class={disabled_button.clone()}>{t.step_over}</button>
+                    <button type="button" class={disabled_button.clone()}>{t.back}</button>
+                    <button type="button" class={disabled_button.clone() + " is-warning"} aria-label={t.stop_label} aria-disabled="true">{t.stop}</button>
+                    <button type="button" class={disabled_button.clone()}>{t.play}</button>
+                    {playback_speed}
+                    <button type="button" class={enabled_button.clone() + " is-success"} onclick={ctx.link().callback(|_| Message::AddAllBreakpoints)}>{t.add_all_breakpoints}</button>
+                    <button type="button" class={enabled_button + " is-error"} onclick={ctx.link().callback(|_| Message::RemoveAllBreakpoints)}>{t.remove_all_breakpoints}</button>
+                    {download_button}
+                    {trace_button.clone()}
+                    {share_button}
+                    {session_buttons}
+                    {reset_button}
+                </>
+            }
+        };
+        html! {
+            <>
+            <div class="controls nes-container with-title" style={style}>
+                <h3 class="title">{t.controls}</h3>
+                <div class="half">
+                    {self.rule_run(ctx)}
+                    {self.run_to_rule(ctx)}
+                    <br/>
+                    {self.breakpoints(ctx)}
+                </div>
+                {buttons}
+                if let Some(link) = &self.state.share_link_fallback {
+                    {Self::copy_fallback(t, link)}
+                }
+                {self.embed_code(ctx)}
+                {progress}
+                {parse_time}
+            </div>
+            </>
+        }
+    }
+
+    /// The iframe HTML embedding the current grammar and input, with its size inputs.
+    fn embed_code(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let code = match self.state.embed_code(ctx.props()) {
+            Some(code) if self.state.show_embed => code,
+            _ => return html! {},
+        };
+        let (width, height) = self.state.embed_size;
+        let size_input = |is_width: bool, label: &'static str, value: u32| {
+            html! {
+                <label>
+                    {label}
+                    <input type="number" class="nes-input" min={EMBED_SIZE_RANGE.0.to_string()}
+                        max={EMBED_SIZE_RANGE.1.to_string()} value={value.to_string()}
+                        oninput={ctx.link().callback(move |e| Message::EmbedSizeChange(is_width, e))} />
+                </label>
+            }
+        };
+        html! {
+            <div class="embed-code">
+                {size_input(true, t.embed_width, width)}
+                {size_input(false, t.embed_height, height)}
+                <label>
+                    {match self.state.embed_copied {
+                        Some(true) => t.embed_copied,
+                        Some(false) => t.copy_fallback,
+                        None => "",
+                    }}
+                    <textarea class="nes-textarea" readonly={true} value={code}
+                        onfocus={Callback::from(|e: FocusEvent| {
+                            if let Some(textarea) = e.target_dyn_into::<HtmlTextAreaElement>() {
+                                textarea.select();
+                            }
+                        })} />
+                </label>
+            </div>
+        }
+    }
+
+    fn header(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let theme_label = match self.state.theme {
+            Theme::Light => t.dark_theme,
+            Theme::Dark => t.light_theme,
+        };
+        let props = ctx.props();
+        let title = props.title.as_deref().unwrap_or(DEFAULT_TITLE);
+        let logo_url = props.logo_url.as_deref().unwrap_or(DEFAULT_LOGO_URL);
+        let languages = Lang::ALL
+            .into_iter()
+            .map(|lang| {
+                html! {
+                    <option value={lang.code()} selected={lang == self.state.lang}>{lang.name()}</option>
+                }
+            })
+            .collect::<Html>();
+        html! {
+            <header class="{ sticky: scrollPos > 50 }">
+                <div class="container">
+                    <div class="nav-brand">
+                    <h1><img src={logo_url.to_owned()} height="50"/>{format!(" {}", title)}</h1>
+                    </div>
+                    <button type="button" class="nes-btn theme-toggle" onclick={ctx.link().callback(|_| Message::ToggleTheme)}>{theme_label}</button>
+                    <div class="nes-select lang-select">
+                        <select aria-label={t.language} onchange={ctx.link().callback(Message::SelectLang)}>
+                            {languages}
+                        </select>
+                    </div>
+                </div>
+            </header>
+        }
+    }
+
+    fn warnings(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        self.state
+            .warnings
+            .iter()
+            .enumerate()
+            .map(|(index, warning)| {
+                html! {
+                    <div class="nes-balloon from-left warning">
+                        <p class="nes-text is-warning">{warning}</p>
+                        <button type="button" class="nes-btn is-warning" onclick={ctx.link().callback(move |_| Message::DismissWarning(index))}>{t.dismiss}</button>
+                    </div>
+                }
+            })
+            .collect()
+    }
+
+    /// Offers to restore the draft found at startup.
+    fn draft(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        if self.state.draft.is_none() {
+            return html! {};
+        }
+        html! {
+            <div class="nes-balloon from-left warning draft" role="alert">
+                <p class="nes-text is-warning">{t.draft_found}</p>
+                <button type="button" class="nes-btn is-primary" onclick={ctx.link().callback(|_| Message::RestoreDraft)}>{t.restore}</button>
+                <button type="button" class="nes-btn" onclick={ctx.link().callback(|_| Message::DiscardDraft)}>{t.discard}</button>
+            </div>
+        }
+    }
+
+    fn success(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        if self.state.success {
+            html! {
+                <div class="nes-balloon from-left success">
+                    <p class="nes-text is-success">{t.parsed_successfully}</p>
+                    <button type="button" class="nes-btn is-success" onclick={ctx.link().callback(|_| Message::DismissSuccess)}>{t.dismiss}</button>
+                </div>
+            }
+        } else {
+            html! {}
+        }
+    }
+
+    /// Asks to confirm clearing the grammar or input textarea.
+    fn confirm_dialog(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        match self.state.confirm_clear {
+            Some(target) => html! {
+                <dialog class="nes-dialog" ref={self.confirm_ref.clone()} aria-labelledby="confirm-title"
+                    onclose={ctx.link().callback(|_| Message::ConfirmDialogClosed)}>
+                    <form method="dialog">
+                    <p class="title" id="confirm-title">{match target {
+                        ClearTarget::Grammar => t.clear_grammar_question,
+                        ClearTarget::Input => t.clear_input_question,
+                    }}</p>
+                    <p>{match target {
+                        ClearTarget::Grammar => t.clear_grammar_warning,
+                        ClearTarget::Input => t.clear_input_warning,
+                    }}</p>
+                    <menu class="dialog-menu">
+                        <button class="nes-btn">{t.cancel}</button>
+                        <button class="nes-btn is-error" onclick={ctx.link().callback(|_| Message::ConfirmClear)}>{t.clear}</button>
+                    </menu>
+                    </form>
+                </dialog>
+            },
+            None => html!(),
+        }
+    }
+
+    fn error_dialog(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        if let Some(err) = &self.state.error {
+            html! {
+            <dialog class="nes-dialog" id="dialog-default" ref={self.modal_ref.clone()} aria-labelledby="dialog-title"
+                onclose={ctx.link().callback(|_| Message::ErrorDialogClosed)}>
+                <form method="dialog">
+                <p class="title" id="dialog-title">{t.error}</p>
+                {self.expected_list()}
+                {self.grammar_error_list(ctx).unwrap_or_else(|| html! { <pre>{err}</pre> })}
+                <menu class="dialog-menu">
+                    <button class="nes-btn">{t.close}</button>
+                </menu>
+                </form>
+            </dialog>
+            }
+        } else {
+            html!()
+        }
+    }
+
+    /// Lists the rules that were expected (and unexpected) where the parsing failed, if known.
+    fn expected_list(&self) -> Html {
+        let t = self.state.lang.messages();
+        match &self.state.expected {
+            Some((positives, negatives)) => html! {
+                <ul class="expected-rules">
+                    if !positives.is_empty() {
+                        <li><span class="nes-text is-success">{t.expected}{" "}</span>{positives.join(", ")}</li>
+                    }
+                    if !negatives.is_empty() {
+                        <li><span class="nes-text is-error">{t.unexpected}{" "}</span>{negatives.join(", ")}</li>
+                    }
+                </ul>
+            },
+            None => html! {},
+        }
+    }
+
+    /// Lists the grammar errors (if any) with their locations,
+    /// which close the dialog and select the location in the grammar when clicked.
+    fn grammar_error_list(&self, ctx: &Context<Self>) -> Option<Html> {
+        let t = self.state.lang.messages();
+        if self.state.grammar_errors.is_empty() {
+            return None;
+        }
+        let errors = self
+            .state
+            .grammar_errors
+            .iter()
+            .enumerate()
+            .map(|(index, error)| {
+                html! {
+                    <li>
+                        <button class="nes-btn grammar-error-location" title={t.show_in_grammar}
+                            onclick={ctx.link().callback(move |_| Message::JumpToGrammarError(index))}>
+                            {format!("{}:{}", error.line, error.col)}
+                        </button>
+                        <pre>{&error.message}</pre>
+                    </li>
+                }
+            })
+            .collect::<Html>();
+        Some(html! {
+            <>
+            <p>{format!("{} {}", self.state.grammar_errors.len(), t.grammar_errors)}</p>
+            <ul class="grammar-errors">{errors}</ul>
+            </>
+        })
+    }
+
+    fn rule_run(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let options = self.state.sorted_breakpoints().into_iter().map(|Breakpoint { rule: r, .. }| {
+            if r == &self.state.to_run {
+                html! {
+                    <option value={r.clone()} selected={true} disabled={self.state.running}>{r}</option>
+                }
+            } else {
+                html! {
+                    <option value={r.clone()} disabled={self.state.running}>{r}</option>
+                }
+            }
+        }).collect::<Html>();
+        if !self.state.has_rules() {
+            return html! {
+                <p class="nes-text is-disabled">{t.no_rules}</p>
+            };
+        }
+        html! {
+            <>
+            <label for="rule_run">{t.select_rule}</label>
+            <div class="nes-select" onchange={ctx.link().callback(Message::SelectRuleToRun)}>
+            <select id="rule_run" ref={self.rule_run_ref.clone()} disabled={!self.state.typed_rule.trim().is_empty()}>
+                {options}
+            </select>
+            </div>
+            <input type="text" class="nes-input typed-rule" placeholder={t.type_rule} aria-label={t.type_rule_label}
+                value={self.state.typed_rule.clone()} readonly={self.state.running} oninput={ctx.link().callback(Message::TypedRuleChange)} />
+            {self.unreachable_note()}
+            </>
+        }
+    }
+
+    /// The dropdown and button to continue to the next breakpoint of a rule.
+    fn run_to_rule(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let targets = self.state.run_to_rule_targets();
+        if targets.is_empty() {
+            return html! {};
+        }
+        let options = targets
+            .iter()
+            .map(|rule| {
+                html! {
+                    <option value={rule.to_string()} selected={*rule == self.state.target_rule}>{*rule}</option>
+                }
+            })
+            .collect::<Html>();
+        html! {
+            <div class="run-to-rule">
+                <label for="run_to_rule">{t.run_to_rule}</label>
+                <div class="nes-select" onchange={ctx.link().callback(Message::SelectTargetRule)}>
+                    <select id="run_to_rule">
+                        {options}
+                    </select>
+                </div>
+                <button type="button" class="nes-btn" title={t.run_to_rule_title}
+                    onclick={ctx.link().callback(|_| Message::RunToRule)}>{t.run_to_rule}</button>
+            </div>
+        }
+    }
+
+    /// Lists the rules that the rule to run can't reach.
+    fn unreachable_note(&self) -> Html {
+        let t = self.state.lang.messages();
+        let root = self.state.reachability_root();
+        let unreachable = analysis::unreachable_rules(&self.state.rule_graph, root);
+        if unreachable.is_empty() {
+            return html! {};
+        }
+        html! {
+            <p class="nes-text is-disabled unreachable-note">
+                {(t.unreachable_from)(root, &unreachable.join(", "))}
+            </p>
+        }
+    }
+
+    fn breakpoints(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let unreachable =
+            analysis::unreachable_rules(&self.state.rule_graph, self.state.reachability_root());
+        let coverage = self.state.coverage();
+        let options = self.state.visible_breakpoints().map(|Breakpoint { enabled: b, rule: r, window, ignore }| {
+            let event = self.state.current_event();
+            let class = match event {
+                Some(DebuggerEvent::Breakpoint(rule, ..)) if rule == r => "nes-text is-primary",
+                Some(DebuggerEvent::RuleExit(rule, _, true)) if rule == r => "nes-text is-success",
+                Some(DebuggerEvent::RuleExit(rule, _, false)) if rule == r => "nes-text is-error",
+                _ if coverage.is_some() && self.state.attempts.contains_key(r) => "nes-text is-success",
+                _ if coverage.is_some() => "nes-text is-disabled",
+                _ if unreachable.contains(&r.as_str()) => "nes-text is-disabled",
+                _ => "nes-text",
+            };
+            html!{
+                <>
+                <label>
+                    <input type="checkbox" class="nes-checkbox" checked={*b} name={r.clone()} aria-label={(t.breakpoint_on)(r)} onchange={ctx.link().callback(Message::ChangeBreakpoint)}
+                        disabled={self.state.running} />
+                    <span class={classes!(class, "rule-link")} title={t.go_to_definition}
+                        onclick={let rule = r.clone(); ctx.link().callback(move |e: MouseEvent| {
+                            // don't toggle the checkbox of the label
+                            e.prevent_default();
+                            Message::JumpToRule(rule.clone())
+                        })}>{r}</span>
+                    {match self.state.rule_modifiers.get(r) {
+                        Some(modifier) if *modifier != RuleModifier::Normal => html! {
+                            <span class="rule-modifier" title={modifier.description()}>{modifier.symbol()}</span>
+                        },
+                        _ => html! {},
+                    }}
+                    if self.state.rule_analysis.nullable.contains(r) {
+                        <span class="rule-modifier" title={t.nullable_title}>{"ε"}</span>
+                    }
+                    if self.state.running {
+                        <span class="nes-text is-disabled">{format!(" (×{})", self.state.hits.get(r).unwrap_or(&0))}</span>
+                    }
+                    // where the current rule is in the input
+                    {match event {
+                        Some(DebuggerEvent::Breakpoint(rule, pos, ..) | DebuggerEvent::RuleExit(rule, pos, _)) if rule == r => html! {
+                            <code class="input-snippet">{position::snippet(self.state.run_input(), *pos, SNIPPET_RADIUS)}</code>
+                        },
+                        _ => html! {},
+                    }}
+                </label>
+                <input type="number" min="0" class="nes-input breakpoint-window" placeholder={t.window_from}
+                    value={window.map(|(start, _)| start.to_string()).unwrap_or_default()}
+                    onchange={let rule = r.clone(); ctx.link().callback(move |e| Message::ChangeBreakpointWindow(rule.clone(), false, e))}
+                    disabled={self.state.running} />
+                <input type="number" min="0" class="nes-input breakpoint-window" placeholder={t.window_to}
+                    value={window.and_then(|(_, end)| (end != usize::MAX).then(|| end.to_string())).unwrap_or_default()}
+                    onchange={let rule = r.clone(); ctx.link().callback(move |e| Message::ChangeBreakpointWindow(rule.clone(), true, e))}
+                    disabled={self.state.running} />
+                <input type="number" min="0" class="nes-input breakpoint-window" placeholder={t.window_skip}
+                    value={if *ignore > 0 { ignore.to_string() } else { String::new() }}
+                    onchange={let rule = r.clone(); ctx.link().callback(move |e| Message::ChangeBreakpointIgnore(rule.clone(), e))}
+                    disabled={self.state.running} />
+                <br/>
+                </>
+            }
+        }).collect::<Html>();
+        html! {
+            <>
+            <label for="breakpoints">{t.breakpoints}</label>
+            <div class="breakpoint-tools">
+                <input type="text" class="nes-input breakpoint-filter" placeholder={t.filter_rules}
+                    value={self.state.breakpoint_filter.clone()} oninput={ctx.link().callback(Message::FilterBreakpoints)} />
+                <button type="button" class="nes-btn" title={t.sort_title} onclick={ctx.link().callback(|_| Message::ToggleSortMode)}>
+                    {match self.state.sort_mode {
+                        SortMode::Definition => t.grammar_order,
+                        SortMode::Alphabetical => t.alphabetical_order,
+                    }}
+                </button>
+                <label class="skip-silent" title={t.skip_silent_title}>
+                    <input type="checkbox" class="nes-checkbox" checked={self.state.skip_silent} disabled={self.state.running}
+                        onchange={ctx.link().callback(|_| Message::ToggleSkipSilent)} />
+                    <span>{t.skip_silent}</span>
+                </label>
+                if let Some((covered, total)) = coverage {
+                    <span class="coverage" title={t.coverage_title}>
+                        {format!("{} {}/{} {}", t.coverage, covered, total, t.coverage_rules)}
+                    </span>
+                }
+            </div>
+            <div id="breakpoints">
+                {options}
+            </div>
+            <p class="first-set" title={t.first_set_title}>
+                {self.state.first_set().unwrap_or_else(|| t.first_set_hint.to_owned())}
+            </p>
+            </>
+        }
+    }
+
+    fn parse_tree_node(node: &ParseTreeNode) -> Html {
+        let label = html! {
+            <>
+            <span class="nes-text is-primary">{&node.rule}</span>
+            {format!(" ({}..{}) {:?}", node.start, node.end, node.text)}
+            </>
+        };
+        if node.children.is_empty() {
+            html! { <li>{label}</li> }
+        } else {
+            html! {
+                <li>
+                <details open=true>
+                <summary>{label}</summary>
+                <ul class="parse-tree">
+                {node.children.iter().map(Self::parse_tree_node).collect::<Html>()}
+                </ul>
+                </details>
+                </li>
+            }
+        }
+    }
+
+    fn stats(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        if self.state.attempts.is_empty() {
+            return html! {};
+        }
+        let header = |order: StatsOrder, title: &str| {
+            let title = if self.state.stats_order == order {
+                format!("{} ▼", title)
+            } else {
+                title.to_owned()
+            };
+            html! {
+                <th class="stats-header" onclick={ctx.link().callback(move |_| Message::SortStats(order))}>{title}</th>
+            }
+        };
+        html! {
+            <div id="stats" style="clear:both; margin:20px">
+            <label for="stats">{t.attempts_per_rule}</label>
+            <div class="nes-table-responsive">
+            <table class="nes-table is-bordered stats">
+                <thead>
+                    <tr>
+                        {header(StatsOrder::Rule, t.rule)}
+                        {header(StatsOrder::Attempts, t.attempts)}
+                    </tr>
+                </thead>
+                <tbody>
+                {self.state.sorted_attempts().into_iter().map(|(rule, count)| html! {
+                    <tr>
+                        <td>{rule}</td>
+                        <td>{count}</td>
+                    </tr>
+                }).collect::<Html>()}
+                </tbody>
+            </table>
+            </div>
+            </div>
+        }
+    }
+
+    fn event_list(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        const ROW_HEIGHT: usize = 24;
+        const HEIGHT: usize = 240;
+        // the final `Eof` isn't an event to stop at
+        let (_, total) = self.state.progress();
+        if !self.state.running || total == 0 {
+            return html! {};
+        }
+        let depths = self.state.event_depths();
+        let list = ui::virtual_list(
+            "event-list",
+            total,
+            ROW_HEIGHT,
+            HEIGHT,
+            self.state.events_scroll,
+            ctx.link().callback(Message::ScrollEvents),
+            |index| {
+                let class = if index == self.state.cursor {
+                    "nes-text is-primary"
+                } else {
+                    "nes-text"
+                };
+                let depth = depths[index];
+                let (indent, capped) = ui::depth_indent(depth);
+                html! {
+                    <span class={classes!(class, "event")}
+                        style={format!("padding-left:{}px", indent)} title={format!("{} {}", t.depth, depth)}
+                        onclick={ctx.link().callback(move |_| Message::JumpTo(index))}>
+                        // the rows beyond the indentation cap show how deep they are
+                        if capped {
+                            <span class="event-depth">{format!("↳{} ", depth)}</span>
+                        }
+                        {format!("{}. {}", index + 1, self.state.events[index])}
+                    </span>
+                }
+            },
+        );
+        html! {
+            <div id="events" style="clear:both; margin:20px">
+            <label for="events">{t.timeline}</label>
+            {list}
+            </div>
+        }
+    }
+
+    fn batch_results(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        if self.state.batch_results.is_empty() {
+            return html! {};
+        }
+        let passed = self
+            .state
+            .batch_results
+            .iter()
+            .filter(|(_, error)| error.is_none())
+            .count();
+        html! {
+            <div id="batch-results" style="clear:both; margin:20px">
+            <label for="batch-results">{format!("{} {}/{} {}", t.batch_run, passed, self.state.batch_results.len(), t.batch_passed)}</label>
+            <div class="nes-table-responsive">
+            <table class="nes-table is-bordered batch-results">
+                <thead>
+                    <tr>
+                        <th>{t.input}</th>
+                        <th>{t.result}</th>
+                    </tr>
+                </thead>
+                <tbody>
+                {self.state.batch_results.iter().map(|(index, error)| {
+                    let index = *index;
+                    let name = self.state.inputs.get(index).map(|(name, _)| name.as_str()).unwrap_or_default();
+                    html! {
+                        <tr>
+                            <td class="batch-input" title={t.select_input} onclick={ctx.link().callback(move |_| Message::SelectInput(index))}>{name}</td>
+                            if let Some(error) = error {
+                                <td class="nes-text is-error" title={error.clone()}>{t.failed}</td>
+                            } else {
+                                <td class="nes-text is-success">{t.passed}</td>
+                            }
+                        </tr>
+                    }
+                }).collect::<Html>()}
+                </tbody>
+            </table>
+            </div>
+            </div>
+        }
+    }
+
+    fn parse_tree(&self) -> Html {
+        let t = self.state.lang.messages();
+        match &self.state.parse_tree {
+            Some(tree) => html! {
+                <div id="parse-tree" style="clear:both; margin:20px">
+                <label for="parse-tree">{t.parse_tree}</label>
+                <ul class="parse-tree">
+                {tree.iter().map(Self::parse_tree_node).collect::<Html>()}
+                </ul>
+                </div>
+            },
+            None => html! {},
+        }
+    }
+
+    fn rule_graph(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        const NODE_WIDTH: usize = 160;
+        const NODE_HEIGHT: usize = 30;
+        const LAYER_HEIGHT: usize = 80;
+        let layers = analysis::graph_layers(&self.state.rule_graph);
+        if layers.is_empty() {
+            return html! {};
+        }
+        // the centers of the nodes
+        let mut positions = HashMap::new();
+        for (depth, layer) in layers.iter().enumerate() {
+            for (index, rule) in layer.iter().enumerate() {
+                positions.insert(
+                    *rule,
+                    (
+                        index * NODE_WIDTH + NODE_WIDTH / 2,
+                        depth * LAYER_HEIGHT + NODE_HEIGHT,
+                    ),
+                );
+            }
+        }
+        let width = layers.iter().map(Vec::len).max().unwrap_or(0) * NODE_WIDTH;
+        let height = layers.len() * LAYER_HEIGHT;
+        let positions = &positions;
+        let edges = self
+            .state
+            .rule_graph
+            .iter()
+            .flat_map(|(rule, callees)| {
+                let (x1, y1) = positions[rule.as_str()];
+                callees
+                    .iter()
+                    .filter(move |callee| *callee != rule)
+                    .map(move |callee| {
+                        let (x2, y2) = positions[callee.as_str()];
+                        // the edges start at the bottom of the caller and end at the top of the callee
+                        html! {
+                            <line x1={x1.to_string()} y1={(y1 + NODE_HEIGHT / 2).to_string()}
+                                x2={x2.to_string()} y2={(y2 - NODE_HEIGHT / 2).to_string()}
+                                class="rule-graph-edge" marker-end="url(#arrow)" />
+                        }
+                    })
+            })
+            .collect::<Html>();
+        let nodes = layers.iter().flatten().map(|rule| {
+            let (x, y) = positions[rule];
+            let class = if *rule == self.state.to_run { "rule-graph-node selected" } else { "rule-graph-node" };
+            let onclick = {
+                let rule = rule.to_string();
+                ctx.link().callback(move |_| Message::SelectRule(rule.clone()))
+            };
+            html! {
+                <g class={class} {onclick}>
+                    <rect x={(x - NODE_WIDTH / 2 + 5).to_string()} y={(y - NODE_HEIGHT / 2).to_string()}
+                        width={(NODE_WIDTH - 10).to_string()} height={NODE_HEIGHT.to_string()} />
+                    <text x={x.to_string()} y={(y + 5).to_string()} text-anchor="middle">{*rule}</text>
+                </g>
+            }
+        }).collect::<Html>();
+        html! {
+            <div id="rule-graph" style="clear:both; margin:20px; overflow:auto">
+                <label for="rule-graph">{t.rule_graph}</label>
+                <svg width={width.to_string()} height={height.to_string()}>
+                    <defs>
+                        <marker id="arrow" viewBox="0 0 10 10" refX="10" refY="5" markerWidth="6" markerHeight="6" orient="auto-start-reverse">
+                            <path d="M 0 0 L 10 5 L 0 10 z" />
+                        </marker>
+                    </defs>
+                    {edges}
+                    {nodes}
+                </svg>
+            </div>
+        }
+    }
+
+    /// The sessions saved in the browser storage, with the buttons to save, load and delete them.
+    fn saved_sessions(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let saved = self.state.saved_sessions.contains(&self.state.session_name);
+        let options = self
+            .state
+            .saved_sessions
+            .iter()
+            .map(|name| {
+                html! {
+                    <option value={name.clone()} selected={*name == self.state.session_name}>{name}</option>
+                }
+            })
+            .collect::<Html>();
+        html! {
+            <aside class="saved-sessions nes-container with-title">
+                <h3 class="title">{t.saved_sessions}</h3>
+                <div class="nes-select">
+                    <select aria-label={t.saved_sessions} onchange={ctx.link().callback(Message::SessionNameChange)}>
+                        <option value="" selected={!saved}>{t.new_session}</option>
+                        {options}
+                    </select>
+                </div>
+                <input type="text" class="nes-input" placeholder={t.session_name} aria-label={t.session_name_label}
+                    value={self.state.session_name.clone()} onchange={ctx.link().callback(Message::SessionNameChange)} />
+                <button type="button" class="nes-btn is-success" onclick={ctx.link().callback(|_| Message::SaveSession)}>{t.save}</button>
+                <button type="button" class={if saved && !self.state.running { "nes-btn" } else { "nes-btn is-disabled" }}
+                    onclick={ctx.link().callback(|_| Message::LoadSavedSession)}>{t.load}</button>
+                <button type="button" class={if saved { "nes-btn is-error" } else { "nes-btn is-disabled" }}
+                    onclick={ctx.link().callback(|_| Message::DeleteSavedSession)}>{t.delete}</button>
+            </aside>
+        }
+    }
+
+    fn rule_diagram(&self) -> Html {
+        let t = self.state.lang.messages();
+        let Some((_, rule_diagram)) = self
+            .state
+            .rule_diagrams
+            .iter()
+            .find(|(rule, _)| *rule == self.state.to_run)
+        else {
+            return html! {};
+        };
+        let (width, height, shapes) = diagram::shapes(rule_diagram);
+        let shapes = shapes.into_iter().map(|shape| match shape {
+            Shape::Box { x, y, width, text, rounded } => {
+                let class = if rounded { "rule-diagram-terminal" } else { "rule-diagram-nonterminal" };
+                html! {
+                    <g class={class}>
+                        <rect x={x.to_string()} y={y.to_string()} width={width.to_string()} height={diagram::BOX_HEIGHT.to_string()}
+                            rx={if rounded { (diagram::BOX_HEIGHT / 2).to_string() } else { "0".to_owned() }} />
+                        <text x={(x + width / 2).to_string()} y={(y + diagram::BOX_HEIGHT / 2 + 4).to_string()} text-anchor="middle">{text}</text>
+                    </g>
+                }
+            }
+            Shape::Line(points) => {
+                let points = points.iter().map(|(x, y)| format!("{},{}", x, y)).collect::<Vec<_>>();
+                html! { <polyline class="rule-diagram-line" points={points.join(" ")} /> }
+            }
+            Shape::Label(x, y, text) => html! {
+                <text class="rule-diagram-label" x={x.to_string()} y={y.to_string()}>{text}</text>
+            },
+        }).collect::<Html>();
+        html! {
+            <div id="rule-diagram" style="clear:both; margin:20px; overflow:auto">
+                <label for="rule-diagram">{format!("{} {}", t.diagram_of, self.state.to_run)}</label>
+                <svg width={width.to_string()} height={height.to_string()}>
+                    {shapes}
+                </svg>
+            </div>
+        }
+    }
+
+    fn ast(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        let Some(ast) = &self.state.ast else {
+            return html! {};
+        };
+        html! {
+            <div id="ast" style="clear:both; margin:20px">
+                <details>
+                    <summary>{t.grammar_ast}</summary>
+                    <label>
+                        <input type="checkbox" class="nes-checkbox" checked={self.state.ast_optimized}
+                            onchange={ctx.link().callback(|_| Message::ToggleAstOptimized)} />
+                        <span>{t.optimized}</span>
+                    </label>
+                    <pre class="ast">{ast}</pre>
+                </details>
+            </div>
+        }
+    }
+
+    fn footer(&self, ctx: &Context<Self>) -> Html {
+        let versions = html! {
+            <p class="versions">{versions()}</p>
+        };
+        if ctx.props().hide_thanks {
+            return html! {
+                <div id="footer" style="clear:both; margin:20px">{versions}</div>
+            };
+        }
+        let t = self.state.lang.messages();
+        html! {
+            <div id="footer" style="clear:both; width: 62%; margin:20px">
+                {versions}
+                <section class="nes-container with-title">
+                <h3 class="title">{t.thanks}</h3>
+                <section class="message-list">
+                <section class="message -left">
+                <i class="nes-ash animate is-small"></i>
+                <div class="nes-balloon from-left">
+                <p>{t.thanks_to}{" "} <a href="https://pest.rs/" target="_blank">{"pest"}</a> <br/> {format!(" {} ", t.and)} <a href="https://docs.rs/pest_debugger/2.5.7/pest_debugger/" target="_blank">{ "pest_debugger" }</a> {format!(" {}", t.well)}</p>
+                </div>
+                </section>
+                <section class="message -right">
+                <div class="nes-balloon from-right">
+                <p><a href="https://github.com/tomtau/pest-web-debug" target="_blank">{t.repo}</a></p>
+                </div>
+                <i class="nes-octocat is-small"></i>
+                </section>
+
+                <section class="message -left">
+                <i class="nes-ash animate is-small"></i>
+                <div class="nes-balloon from-left">
+                <p><a href="https://nostalgic-css.github.io/NES.css/" target="_blank">{"NES.css"}</a>{", "}<br /> <a href="https://github.com/sbeckeriv/pest_format" target="_blank">{ "sbeckeriv's pest_format layout" }</a><br />{format!("{} ", t.and)} <a href="https://github.com/yewstack/yew" target="_blank">{ "yew" }</a></p>
+                </div>
+                </section>
+                </section>
+                </section>
+                </div>
+        }
+    }
+}
+
+impl Component for App {
+    type Message = Message;
+    type Properties = AppProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let cb = {
+            let link = ctx.link().clone();
+            move |(epoch, e)| link.send_message(Self::Message::WorkerMsg(epoch, e))
+        };
+        let mut worker = WorkerBridge {
+            bridge: Worker::bridge(Rc::new(cb)),
+            epoch: 0,
+        };
+        let keydown = {
+            let link = ctx.link().clone();
+            EventListener::new(&gloo_utils::document(), "keydown", move |e| {
+                if let Some(e) = e.dyn_ref::<KeyboardEvent>() {
+                    if let Some(msg) = shortcut(e) {
+                        e.prevent_default();
+                        link.send_message(msg);
+                    }
+                }
+            })
+        };
+        let host_commands = {
+            let link = ctx.link().clone();
+            EventListener::new(&gloo_utils::window(), "message", move |e| {
+                if let Some(e) = e.dyn_ref::<MessageEvent>() {
+                    // only the page embedding the debugger can control it
+                    let parent = gloo_utils::window().parent().ok().flatten();
+                    if e.source().map(JsValue::from) != parent.map(JsValue::from) {
+                        return;
+                    }
+                    if let Some(command) = HostCommand::from_js(&e.data()) {
+                        link.send_message(Message::Host(command));
+                    }
+                }
+            })
+        };
+        let mut state = AppState::default();
+        let props = ctx.props();
+        if let Some(grammar) = &props.grammar {
+            state.grammar = grammar.clone();
+        }
+        if let Some(input) = &props.input {
+            state.set_input(input.clone());
+        }
+        if let Some(to_run) = &props.to_run {
+            state.to_run = to_run.clone();
+        }
+        // a shared link takes precedence over the preset
+        if let Some(shared) = SharedState::from_location() {
+            state.grammar = shared.grammar;
+            state.set_input(shared.input);
+            state.to_run = shared.to_run;
+        }
+        // e.g. `?autorun=1&rule=ident_list` runs the rule once the grammar is loaded
+        let params = gloo_utils::window()
+            .location()
+            .search()
+            .ok()
+            .and_then(|search| UrlSearchParams::new_with_str(&search).ok());
+        if let Some(params) = params {
+            if let Some(rule) = params.get("rule") {
+                state.to_run = rule;
+            }
+            state.autorun = params.get("autorun").as_deref() == Some("1");
+        }
+        if let Ok(theme) = LocalStorage::get(THEME_KEY) {
+            state.theme = theme;
+        }
+        if let Ok(lang) = LocalStorage::get(LANG_KEY) {
+            state.lang = lang;
+            // the default input tab is named in the language
+            state.inputs[0].0 = (state.lang.messages().input_name)(1);
+        }
+        if let Ok(split_ratio) = LocalStorage::get(SPLIT_RATIO_KEY) {
+            state.split_ratio = split_ratio;
+        }
+        if let Ok(indent) = LocalStorage::get(INDENT_KEY) {
+            state.indent = indent;
+        }
+        if let Ok(skip_silent) = LocalStorage::get(SKIP_SILENT_KEY) {
+            state.skip_silent = skip_silent;
+        }
+        if let Ok(normalize) = LocalStorage::get(NORMALIZE_LINE_ENDINGS_KEY) {
+            state.normalize_line_endings = normalize;
+        }
+        if state.normalize_line_endings {
+            state.normalize_inputs();
+            worker.send(WorkerInput::SetNormalizeLineEndings(true));
+        }
+        // the draft is only offered, as the preset or shared link may be wanted instead
+        if let Ok(draft) = LocalStorage::get(DRAFT_KEY) {
+            state.draft = state.unsaved_draft(draft);
+        }
+        worker.send(WorkerInput::LoadGrammar(state.grammar.clone()));
+        worker.send(WorkerInput::LoadInput(state.input().to_owned()));
+        store::list_sessions(ctx.link().callback(Message::SavedSessionsListed));
+        Self {
+            grammar_ref: NodeRef::default(),
+            input_ref: NodeRef::default(),
+            modal_ref: NodeRef::default(),
+            rule_run_ref: NodeRef::default(),
+            show_error: false,
+            confirm_ref: NodeRef::default(),
+            show_confirm: false,
+            error_opener: None,
+            _keydown: keydown,
+            _host_commands: host_commands,
+            grammar_debounce: None,
+            input_debounce: None,
+            playback: None,
+            success_timeout: None,
+            grammar_highlight_ref: NodeRef::default(),
+            grammar_view_ref: NodeRef::default(),
+            active_rule_ref: NodeRef::default(),
+            scrolled_rule: None,
+            panes_ref: NodeRef::default(),
+            split_dragging: false,
+            grammar_maximize_ref: NodeRef::default(),
+            input_maximize_ref: NodeRef::default(),
+            focus_after_render: None,
+            drop_target: None,
+            worker,
+            state,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            Self::Message::GrammarChange => {
+                if let Some(input) = self.grammar_ref.cast::<HtmlTextAreaElement>() {
+                    self.state.grammar = input.value();
+                    let link = ctx.link().clone();
+                    // replacing the timeout cancels the pending one
+                    self.grammar_debounce = Some(Timeout::new(DEBOUNCE_MS, move || {
+                        link.send_message(Message::FlushGrammar)
+                    }));
+                }
+                true
+            }
+            Self::Message::GrammarKeyDown(e) => {
+                // Tab indents (and Shift+Tab dedents) instead of moving the focus
+                if e.key() != "Tab" || e.ctrl_key() || e.alt_key() || e.meta_key() {
+                    return false;
+                }
+                let Some(textarea) = self.grammar_ref.cast::<HtmlTextAreaElement>() else {
+                    return false;
+                };
+                e.prevent_default();
+                let text = textarea.value();
+                // the selection is in UTF-16 code units
+                let offset = |utf16: Option<u32>| {
+                    position::byte_offset_from_utf16(&text, utf16.unwrap_or(0) as usize)
+                };
+                let start = offset(textarea.selection_start().ok().flatten());
+                let end = offset(textarea.selection_end().ok().flatten()).max(start);
+                let edit = if e.shift_key() {
+                    position::dedent(&text, start, end, self.state.indent.width())
+                } else {
+                    position::indent(&text, start, end, &self.state.indent.unit())
+                };
+                let utf16 = |text: &str, idx: usize| position::utf16_offset(text, idx) as u32;
+                if textarea
+                    .set_range_text_with_start_and_end(
+                        &edit.text,
+                        utf16(&text, edit.start),
+                        utf16(&text, edit.end),
+                    )
+                    .is_err()
+                {
+                    return false;
+                }
+                let text = textarea.value();
+                let _ = textarea.set_selection_range(
+                    utf16(&text, edit.selection.0),
+                    utf16(&text, edit.selection.1),
+                );
+                // setting the text doesn't fire an input event
+                Component::update(self, ctx, Message::GrammarChange)
+            }
+            Self::Message::SelectIndent(e) => {
+                let Some(select) = e.target_dyn_into::<HtmlSelectElement>() else {
+                    return false;
+                };
+                let value = select.value();
+                let Some(indent) = Indent::ALL
+                    .into_iter()
+                    .find(|indent| indent.code() == value)
+                else {
+                    return false;
+                };
+                self.state.indent = indent;
+                let _ = LocalStorage::set(INDENT_KEY, indent);
+                true
+            }
+            Self::Message::FlushGrammar => {
+                self.flush_grammar();
+                false
+            }
+            Self::Message::GrammarScroll => {
+                // the highlighted grammar follows the textarea
+                if let (Some(textarea), Some(highlight)) = (
+                    self.grammar_ref.cast::<HtmlTextAreaElement>(),
+                    self.grammar_highlight_ref.cast::<HtmlElement>(),
+                ) {
+                    highlight.set_scroll_top(textarea.scroll_top());
+                    highlight.set_scroll_left(textarea.scroll_left());
+                }
+                false
+            }
+            Self::Message::InputChange => {
+                if let Some(input) = self.input_ref.cast::<HtmlTextAreaElement>() {
+                    self.state.set_input(input.value());
+                    if self.state.input() != input.value() {
+                        // the line endings were normalized
+                        input.set_value(self.state.input());
+                    }
+                    self.state.input_file = None;
+                    self.state.error_pos = None;
+                    self.state.run_selection = None;
+                    self.state.partial_parse = None;
+                    let link = ctx.link().clone();
+                    self.input_debounce = Some(Timeout::new(DEBOUNCE_MS, move || {
+                        link.send_message(Message::FlushInput)
+                    }));
+                }
+                Component::update(self, ctx, Message::InputCaret);
+                true
+            }
+            Self::Message::InputCaret => {
+                let caret = self
+                    .input_ref
+                    .cast::<HtmlTextAreaElement>()
+                    .and_then(|textarea| textarea.selection_start().ok()?)
+                    // the DOM reports the caret in UTF-16 code units
+                    .map(|caret| {
+                        position::byte_offset_from_utf16(self.state.input(), caret as usize)
+                    });
+                if caret == self.state.input_caret {
+                    return false;
+                }
+                self.state.input_caret = caret;
+                true
+            }
+            Self::Message::FlushInput => {
+                self.flush_input();
+                false
+            }
+            Self::Message::SelectRuleToRun(e) => {
+                if let Some(input) = e.target_dyn_into::<HtmlSelectElement>() {
+                    if self.state.select_rule_to_run(&input.value()) {
+                        let _ = self.state.shared().update_location();
+                    }
+                }
+                true
+            }
+            Self::Message::ToggleSortMode => {
+                self.state.sort_mode = match self.state.sort_mode {
+                    SortMode::Definition => SortMode::Alphabetical,
+                    SortMode::Alphabetical => SortMode::Definition,
+                };
+                true
+            }
+            Self::Message::ScrollEvents(e) => {
+                match e
+                    .target()
+                    .and_then(|target| target.dyn_into::<Element>().ok())
+                {
+                    Some(list) => {
+                        self.state.events_scroll = list.scroll_top().max(0) as usize;
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Self::Message::FilterBreakpoints(e) => {
+                if let Ok(input) = e.target().unwrap().dyn_into::<HtmlInputElement>() {
+                    self.state.breakpoint_filter = input.value();
+                }
+                true
+            }
+            Self::Message::ChangeBreakpoint(e) => {
+                if let Ok(input) = e.target().unwrap().dyn_into::<HtmlInputElement>() {
+                    let rule = input.name();
+                    let skipped = self.state.is_skipped(&rule);
+                    if let Some(breakpoint) =
+                        self.state.breakpoints.iter_mut().find(|b| b.rule == rule)
+                    {
+                        breakpoint.enabled = input.checked();
+                        if !breakpoint.enabled {
+                            self.worker.send(WorkerInput::DeleteBreakpoint(rule));
+                        } else if !skipped {
+                            self.worker.send(breakpoint.worker_input());
+                        }
+                    }
+                }
+                true
+            }
+            Self::Message::ChangeBreakpointWindow(rule, is_end, e) => {
+                let skipped = self.state.is_skipped(&rule);
+                if let Ok(input) = e.target().unwrap().dyn_into::<HtmlInputElement>() {
+                    if let Some(breakpoint) =
+                        self.state.breakpoints.iter_mut().find(|b| b.rule == rule)
+                    {
+                        let value = input.value().trim().parse::<usize>().ok();
+                        let (mut start, mut end) = breakpoint.window.unwrap_or((0, usize::MAX));
+                        if is_end {
+                            end = value.unwrap_or(usize::MAX);
+                        } else {
+                            start = value.unwrap_or(0);
+                        }
+                        breakpoint.window = if (start, end) == (0, usize::MAX) {
+                            None
+                        } else {
+                            Some((start, end))
+                        };
+                        if breakpoint.enabled && !skipped {
+                            self.worker.send(breakpoint.worker_input());
+                        }
+                    }
+                }
+                true
+            }
+            Self::Message::ChangeBreakpointIgnore(rule, e) => {
+                if let Ok(input) = e.target().unwrap().dyn_into::<HtmlInputElement>() {
+                    if let Some(breakpoint) =
+                        self.state.breakpoints.iter_mut().find(|b| b.rule == rule)
+                    {
+                        breakpoint.ignore = input.value().trim().parse::<usize>().unwrap_or(0);
+                        self.worker
+                            .send(WorkerInput::SetBreakpointIgnore(rule, breakpoint.ignore));
+                    }
+                }
+                true
+            }
+            Self::Message::AddAllBreakpoints => {
+                for breakpoint in self.state.breakpoints.iter_mut() {
+                    breakpoint.enabled = true;
+                }
+                // the position windows and skipped rules are kept
+                // (unlike with `WorkerInput::AddAllRulesBreakpoints`)
+                self.sync_breakpoints();
+                true
+            }
+            Self::Message::RemoveAllBreakpoints => {
+                for breakpoint in self.state.breakpoints.iter_mut() {
+                    breakpoint.enabled = false;
+                }
+                self.worker.send(WorkerInput::DeleteAllBreakpoints);
+                true
+            }
+            Self::Message::Run => {
+                self.start_run(None);
+                true
+            }
+            Self::Message::RunToEnd => {
+                self.start_run(None);
+                self.state.run_to_end = self.state.running;
+                true
+            }
+            Self::Message::RunAll => {
+                self.start_batch();
+                true
+            }
+            Self::Message::RunSelection => {
+                let selection = self
+                    .input_ref
+                    .cast::<HtmlTextAreaElement>()
+                    .and_then(|textarea| {
+                        let start = textarea.selection_start().ok()??;
+                        let end = textarea.selection_end().ok()??;
+                        Some((start as usize, end as usize))
+                    });
+                match selection {
+                    // the DOM reports the selection in UTF-16 code units
+                    Some((start, end)) if start < end => {
+                        let input = self.state.input();
+                        let start = position::byte_offset_from_utf16(input, start);
+                        let end = position::byte_offset_from_utf16(input, end);
+                        self.start_run(Some((start, end)));
+                        true
+                    }
+                    _ => false,
+                }
+            }
+            Self::Message::SelectTargetRule(e) => {
+                if let Ok(select) = e.target().unwrap().dyn_into::<HtmlSelectElement>() {
+                    self.state.target_rule = select.value();
+                }
+                false
+            }
+            Self::Message::RunToRule => {
+                let targets = self.state.run_to_rule_targets();
+                let rule = if targets.contains(&self.state.target_rule.as_str()) {
+                    self.state.target_rule.clone()
+                } else {
+                    match targets.first() {
+                        Some(rule) => rule.to_string(),
+                        None => return false,
+                    }
+                };
+                if self.state.running {
+                    let next = self.state.next_hit_of(&rule);
+                    self.step_to(ctx, next);
+                } else {
+                    // the session skips to the rule's first breakpoint once it's received
+                    self.start_run(None);
+                    if self.state.running {
+                        self.state.run_to_rule = Some(rule);
+                    }
+                }
+                true
+            }
+            Self::Message::RunToCursor => {
+                let caret = self
+                    .input_ref
+                    .cast::<HtmlTextAreaElement>()
+                    .and_then(|textarea| textarea.selection_start().ok()?);
+                match caret {
+                    // the DOM reports the caret in UTF-16 code units
+                    Some(caret) => {
+                        let pos =
+                            position::byte_offset_from_utf16(self.state.input(), caret as usize);
+                        self.start_run_to_position(pos);
+                        true
+                    }
+                    None => false,
+                }
+            }
+            Self::Message::WorkerMsg(epoch, msg) => {
+                // the events of a run are outdated if the grammar, input or session
+                // changed after it was started (the grammar responses are never outdated,
+                // as the latest grammar's come last)
+                let grammar_response = matches!(
+                    msg,
+                    DebuggerEvent::Rules(_)
+                        | DebuggerEvent::RuleGraph(_)
+                        | DebuggerEvent::RuleAnalysis(_)
+                        | DebuggerEvent::RuleDiagrams(_)
+                        | DebuggerEvent::Ast(_)
+                        | DebuggerEvent::Warning(_)
+                        | DebuggerEvent::GrammarErrors(_)
+                );
+                if epoch != self.worker.epoch && !grammar_response {
+                    return false;
+                }
+                match msg {
+                    DebuggerEvent::Rules(rules) => {
+                        self.state.rule_modifiers = rules.iter().cloned().collect();
+                        let rules = rules.into_iter().map(|(rule, _)| rule).collect::<Vec<_>>();
+                        // keep the settings of the breakpoints whose rules still exist
+                        let mut old = std::mem::take(&mut self.state.breakpoints);
+                        self.state.breakpoints = rules
+                            .iter()
+                            .map(|x| match old.iter().position(|b| &b.rule == x) {
+                                Some(index) => old.swap_remove(index),
+                                None => Breakpoint::new(x.clone()),
+                            })
+                            .collect();
+                        // the worker still has the breakpoints of the removed rules
+                        self.sync_breakpoints();
+                        if !rules.contains(&self.state.to_run) {
+                            // e.g. no rule is left to run after clearing the grammar
+                            self.state.to_run = rules.first().cloned().unwrap_or_default();
+                            let _ = self.state.shared().update_location();
+                        }
+                        self.state.error = None;
+                        self.state.error_pos = None;
+                        self.state.expected = None;
+                        self.state.grammar_errors.clear();
+                        // the results are stale for the new grammar
+                        self.state.batch_results.clear();
+                        // the warnings (if any) are sent after the rules
+                        self.state.warnings.clear();
+                        self.worker
+                            .send(WorkerInput::RequestAst(self.state.ast_optimized));
+                        if std::mem::take(&mut self.state.autorun) {
+                            self.start_run(None);
+                        }
+                    }
+                    DebuggerEvent::RuleGraph(graph) => {
+                        self.state.rule_graph = graph;
+                    }
+                    DebuggerEvent::RuleAnalysis(analysis) => {
+                        self.state.rule_analysis = analysis;
+                    }
+                    DebuggerEvent::RuleDiagrams(diagrams) => {
+                        self.state.rule_diagrams = diagrams;
+                    }
+                    DebuggerEvent::Ast(ast) => {
+                        self.state.ast = Some(ast);
+                    }
+                    DebuggerEvent::Warning(warning) => {
+                        // the input warnings are sent again on each input change
+                        if self.state.warnings.contains(&warning) {
+                            return false;
+                        }
+                        self.state.warnings.push(warning);
+                    }
+                    DebuggerEvent::GrammarErrors(errors) => {
+                        // the modal shows all errors, while the first one is marked
+                        // next to the grammar
+                        self.state.error = Some(GrammarError::summary(&errors));
+                        self.state.error_pos = None;
+                        self.state.expected = None;
+                        self.state.grammar_errors = errors;
+                        self.post_error();
+                    }
+                    DebuggerEvent::Expected(positives, negatives) => {
+                        // the error follows
+                        self.state.expected = Some((positives, negatives));
+                    }
+                    DebuggerEvent::Error(e, pos) => {
+                        self.state.error = Some(e);
+                        self.state.error_pos = pos;
+                        self.post_error();
+                        // the error is the last event of a failed run
+                        self.finish_run_to_end(ctx);
+                    }
+                    DebuggerEvent::Aborted(steps) => {
+                        self.state.error = Some((self.state.lang.messages().aborted)(steps));
+                        self.state.error_pos = None;
+                        self.post_error();
+                        self.finish_run_to_end(ctx);
+                    }
+                    DebuggerEvent::ParseTreeJson(tree) => match serde_json::from_str(&tree) {
+                        Ok(tree) => self.state.parse_tree = Some(tree),
+                        Err(e) => self.state.error = Some(e.to_string()),
+                    },
+                    DebuggerEvent::PartialParse(pos) => {
+                        self.state.partial_parse = Some(pos);
+                    }
+                    DebuggerEvent::Timing(time) => {
+                        self.state.parse_time = Some(time);
+                    }
+                    DebuggerEvent::Truncated(total) => {
+                        self.state.truncated = Some(total);
+                    }
+                    DebuggerEvent::BatchResult(index, error) => {
+                        self.state.batch_results.push((index, error));
+                    }
+                    // the events of a stopped session are dropped
+                    _ if !self.state.running => {}
+                    DebuggerEvent::Correction(index, pos) => {
+                        self.state.correct(index, pos);
+                    }
+                    _ => {
+                        if let DebuggerEvent::Breakpoint(rule, ..) = &msg {
+                            *self.state.attempts.entry(rule.clone()).or_default() += 1;
+                        }
+                        self.state.events.push_back(msg);
+                        self.state.count_hits();
+                        let last = self.state.events.len() - 1;
+                        if self.state.events.back() == Some(&DebuggerEvent::Eof) {
+                            self.finish_run_to_end(ctx);
+                        } else if matches!(
+                            (&self.state.run_to_rule, self.state.events.back()),
+                            (Some(rule), Some(DebuggerEvent::Breakpoint(hit, ..))) if rule == hit
+                        ) {
+                            self.state.run_to_rule = None;
+                            self.state.jump_to(last);
+                            self.post_breakpoint();
+                        } else if last == 0 && self.state.run_to_rule.is_none() {
+                            // the session starts at the first event
+                            self.post_breakpoint();
+                        }
+                    }
+                }
+                true
+            }
+            Self::Message::Continue => {
+                self.step_to(ctx, self.state.cursor + 1);
+                true
+            }
+            Self::Message::StepOver => {
+                if !self.state.events.is_empty() {
+                    let depths = self.state.event_depths();
+                    let depth = depths.get(self.state.cursor).copied().unwrap_or(0);
+                    let next = (self.state.cursor + 1..depths.len())
+                        .find(|index| depths[*index] <= depth)
+                        .unwrap_or(depths.len());
+                    self.step_to(ctx, next);
+                }
+                true
+            }
+            Self::Message::JumpTo(index) => {
+                if self.state.jump_to(index) {
+                    self.post_breakpoint();
+                    true
+                } else {
+                    false
+                }
+            }
+            Self::Message::Previous => {
+                self.state.cursor = self.state.cursor.saturating_sub(1);
+                self.state.count_hits();
+                true
+            }
+            Self::Message::DownloadTree => {
+                if let Some(tree) = &self.state.parse_tree {
+                    let result = serde_json::to_string_pretty(tree)
+                        .map_err(|e| e.to_string())
+                        .and_then(|json| {
+                            download("parse_tree.json", &json, "application/json")
+                                .map_err(|e| format!("{:?}", e))
+                        });
+                    if let Err(e) = result {
+                        self.report_error(e);
+                    }
+                }
+                true
+            }
+            Self::Message::ExportTrace => {
+                if let Err(e) = download("trace.txt", &self.state.trace_log(), "text/plain") {
+                    self.report_error(format!("{:?}", e));
+                }
+                false
+            }
+            Self::Message::CopyShareLink => {
+                match self.state.shared().link() {
+                    Ok(link) => {
+                        let text = link.clone();
+                        copy_to_clipboard(
+                            &link,
+                            ctx.link().callback(move |copied: bool| {
+                                // the link is shown to copy by hand if it failed
+                                Message::ShareLinkCopied((!copied).then(|| text.clone()))
+                            }),
+                        );
+                    }
+                    Err(e) => self.report_error(format!("{:?}", e)),
+                }
+                true
+            }
+            Self::Message::ShareLinkCopied(fallback) => {
+                self.state.share_link_fallback = fallback;
+                true
+            }
+            Self::Message::GetEmbedCode => {
+                self.state.show_embed = true;
+                if let Some(code) = self.state.embed_code(ctx.props()) {
+                    copy_to_clipboard(&code, ctx.link().callback(Message::EmbedCopied));
+                }
+                true
+            }
+            Self::Message::EmbedSizeChange(is_width, e) => {
+                if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                    // out-of-range sizes are ignored until fixed
+                    if let Ok(size) = input.value().parse::<u32>() {
+                        if (EMBED_SIZE_RANGE.0..=EMBED_SIZE_RANGE.1).contains(&size) {
+                            if is_width {
+                                self.state.embed_size.0 = size;
+                            } else {
+                                self.state.embed_size.1 = size;
+                            }
+                            // the copied code is outdated
+                            self.state.embed_copied = None;
+                        }
+                    }
+                }
+                true
+            }
+            Self::Message::EmbedCopied(copied) => {
+                self.state.embed_copied = Some(copied);
+                true
+            }
+            Self::Message::ExportSession => {
+                let result = serde_json::to_string_pretty(&self.state.session())
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| {
+                        download("session.json", &json, "application/json")
+                            .map_err(|e| format!("{:?}", e))
+                    });
+                if let Err(e) = result {
+                    self.report_error(e);
+                }
+                true
+            }
+            Self::Message::ImportSession(e) => {
+                if let Ok(input) = e.target().unwrap().dyn_into::<HtmlInputElement>() {
+                    if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                        let callback = ctx.link().callback(Message::SessionLoaded);
+                        if let Err(e) = read_file(&file, callback) {
+                            self.report_error(format!("{:?}", e));
+                        }
+                    }
+                    // allows picking the same file again
+                    input.set_value("");
+                }
+                true
+            }
+            Self::Message::SessionNameChange(e) => {
+                if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                    self.state.session_name = input.value();
+                } else if let Some(select) = e.target_dyn_into::<HtmlSelectElement>() {
+                    self.state.session_name = select.value();
+                }
+                true
+            }
+            Self::Message::SaveSession => {
+                let name = self.state.session_name.trim().to_owned();
+                if name.is_empty() {
+                    return false;
+                }
+                // the pending edits are saved too
+                self.flush_grammar();
+                self.flush_input();
+                store::save_session(
+                    &name,
+                    &self.state.session(),
+                    ctx.link().callback(Message::SavedSessionsChanged),
+                );
+                false
+            }
+            Self::Message::LoadSavedSession => {
+                if self.state.running
+                    || !self.state.saved_sessions.contains(&self.state.session_name)
+                {
+                    return false;
+                }
+                store::load_session(
+                    &self.state.session_name,
+                    ctx.link().callback(Message::SavedSessionLoaded),
+                );
+                false
+            }
+            Self::Message::TypedRuleChange(e) => {
+                if let Some(input) = e.target_dyn_into::<HtmlInputElement>() {
+                    self.state.typed_rule = input.value();
+                }
+                true
+            }
+            Self::Message::CopyPosition => {
+                if let Some(summary) = self.state.position_summary() {
+                    let text = summary.clone();
+                    copy_to_clipboard(
+                        &summary,
+                        ctx.link().callback(move |copied: bool| {
+                            // the text is shown to copy by hand if it failed
+                            Message::PositionCopied((!copied).then(|| text.clone()))
+                        }),
+                    );
+                }
+                false
+            }
+            Self::Message::PositionCopied(fallback) => {
+                self.state.copy_fallback = fallback;
+                true
+            }
+            Self::Message::RestoreDraft => {
+                if self.state.running {
+                    return false;
+                }
+                if let Some(draft) = self.state.draft.take() {
+                    self.load_session(draft);
+                }
+                true
+            }
+            Self::Message::DiscardDraft => {
+                self.state.draft = None;
+                LocalStorage::delete(DRAFT_KEY);
+                true
+            }
+            Self::Message::DeleteSavedSession => {
+                if !self.state.saved_sessions.contains(&self.state.session_name) {
+                    return false;
+                }
+                store::delete_session(
+                    &self.state.session_name,
+                    ctx.link().callback(Message::SavedSessionsChanged),
+                );
+                false
+            }
+            Self::Message::SavedSessionsListed(result) => {
+                match result {
+                    Ok(names) => self.state.saved_sessions = names,
+                    Err(e) => self.report_warning(e),
+                }
+                true
+            }
+            Self::Message::SavedSessionsChanged(result) => {
+                match result {
+                    Ok(()) => {
+                        store::list_sessions(ctx.link().callback(Message::SavedSessionsListed))
+                    }
+                    Err(e) => self.report_warning(e),
+                }
+                true
+            }
+            Self::Message::SavedSessionLoaded(result) => {
+                match result {
+                    Ok(session) => self.load_session(session),
+                    Err(e) => self.report_warning(e),
+                }
+                true
+            }
+            Self::Message::SessionLoaded(result) => {
+                match result.and_then(|json| {
+                    serde_json::from_str::<Session>(&json).map_err(|e| e.to_string())
+                }) {
+                    Ok(session) => self.load_session(session),
+                    Err(e) => self.report_error(e),
+                }
+                true
+            }
+            Self::Message::UploadGrammar(e) => {
+                if let Ok(input) = e.target().unwrap().dyn_into::<HtmlInputElement>() {
+                    if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                        let callback = ctx.link().callback(Message::GrammarLoaded);
+                        if let Err(e) = read_file(&file, callback) {
+                            self.report_error(format!("{:?}", e));
+                        }
+                    }
+                    input.set_value("");
+                }
+                true
+            }
+            Self::Message::GrammarUrlChange(e) => {
+                if let Ok(input) = e.target().unwrap().dyn_into::<HtmlInputElement>() {
+                    self.state.grammar_url = input.value();
+                }
+                false
+            }
+            Self::Message::FetchGrammar => {
+                let url = self.state.grammar_url.trim().to_owned();
+                if !url.is_empty() {
+                    ctx.link()
+                        .send_future(async move { Message::GrammarLoaded(fetch_text(&url).await) });
+                }
+                false
+            }
+            Self::Message::Host(command) => match command {
+                HostCommand::LoadGrammar { text } if !self.state.running => {
+                    Component::update(self, ctx, Message::GrammarLoaded(Ok(text)))
+                }
+                HostCommand::LoadGrammar { .. } => false,
+                HostCommand::Run { .. } if self.state.running => {
+                    // the selected rule is kept until the session is stopped
+                    let message = "a debugger session is already running".to_owned();
+                    let _ = HostEvent::Error { message }.post();
+                    false
+                }
+                HostCommand::Run { rule } => {
+                    if self.state.select_rule_to_run(&rule) {
+                        self.start_run(None);
+                    } else {
+                        let message = format!("unknown rule to run: {}", rule);
+                        let _ = HostEvent::Error { message }.post();
+                    }
+                    true
+                }
+                HostCommand::Continue => Component::update(self, ctx, Message::Continue),
+            },
+            Self::Message::GrammarLoaded(result) => {
+                match result {
+                    Ok(grammar) => {
+                        self.state.grammar = grammar;
+                        if let Some(input) = self.grammar_ref.cast::<HtmlTextAreaElement>() {
+                            input.set_value(&self.state.grammar);
+                        }
+                        self.worker
+                            .send(WorkerInput::LoadGrammar(self.state.grammar.clone()));
+                        let _ = self.state.shared().update_location();
+                    }
+                    Err(e) => self.report_error(e),
+                }
+                true
+            }
+            Self::Message::SelectInput(index) => {
+                if self.state.running || index >= self.state.inputs.len() {
+                    return false;
+                }
+                self.select_input(index);
+                true
+            }
+            Self::Message::AddInput => {
+                if self.state.running {
+                    return false;
+                }
+                let name = (self.state.inputs.len() + 1..)
+                    .map(self.state.lang.messages().input_name)
+                    .find(|name| self.state.inputs.iter().all(|(other, _)| other != name))
+                    .unwrap_or_default();
+                self.state.inputs.push((name, String::new()));
+                self.select_input(self.state.inputs.len() - 1);
+                true
+            }
+            Self::Message::CloseInput(index) => {
+                if self.state.running || self.state.inputs.len() <= 1 {
+                    return false;
+                }
+                self.flush_input();
+                self.state.inputs.remove(index);
+                // the results refer to the inputs by their indices
+                self.state.batch_results.clear();
+                let active = match self.state.active_input {
+                    active if active > index => active - 1,
+                    active => active.min(self.state.inputs.len() - 1),
+                };
+                self.select_input(active);
+                true
+            }
+            Self::Message::UploadInput(e) => {
+                if let Ok(input) = e.target().unwrap().dyn_into::<HtmlInputElement>() {
+                    if let Some(file) = input.files().and_then(|files| files.get(0)) {
+                        let name = file.name();
+                        let callback = ctx
+                            .link()
+                            .callback(move |result| Message::InputLoaded(name.clone(), result));
+                        if let Err(e) = read_file(&file, callback) {
+                            self.report_error(format!("{:?}", e));
+                        }
+                    }
+                    input.set_value("");
+                }
+                true
+            }
+            Self::Message::InputLoaded(name, result) => {
+                match result {
+                    Ok(input) => {
+                        self.state.set_input(input);
+                        self.state.input_file = Some(name);
+                        self.state.error_pos = None;
+                        self.state.run_selection = None;
+                        self.state.partial_parse = None;
+                        self.worker
+                            .send(WorkerInput::LoadInput(self.state.input().to_owned()));
+                        // large inputs would make for unwieldy URLs
+                        if self.state.input().len() <= LARGE_INPUT {
+                            let _ = self.state.shared().update_location();
+                        }
+                    }
+                    Err(e) => self.report_error(e),
+                }
+                true
+            }
+            Self::Message::JumpToRule(rule) => {
+                if let Some((start, end)) = position::rule_definition(&self.state.grammar, &rule) {
+                    self.select_grammar(start, end);
+                }
+                // its FIRST set is shown too
+                self.state.first_set_rule = Some(rule);
+                true
+            }
+            Self::Message::JumpToGrammarError(index) => {
+                if let Some(error) = self.state.grammar_errors.get(index) {
+                    let grammar = &self.state.grammar;
+                    let start = position::byte_offset(grammar, error.line, error.col);
+                    let (_, current, _) = position::split_at_char(grammar, start);
+                    // the dialog is closed, but the focus goes to the grammar
+                    self.error_opener = None;
+                    self.select_grammar(start, start + current.len());
+                }
+                false
+            }
+            Self::Message::SelectRule(rule) => {
+                if self.state.running {
+                    return false;
+                }
+                self.state.to_run = rule;
+                let _ = self.state.shared().update_location();
+                true
+            }
+            Self::Message::AskClear(target) => {
+                if self.state.running {
+                    return false;
+                }
+                self.state.confirm_clear = Some(target);
+                self.show_confirm = true;
+                true
+            }
+            Self::Message::ConfirmClear => {
+                if let Some(target) = self.state.confirm_clear.take() {
+                    self.clear(target);
+                }
+                true
+            }
+            Self::Message::ConfirmDialogClosed => {
+                self.state.confirm_clear = None;
+                true
+            }
+            Self::Message::ErrorDialogClosed => {
+                if let Some(opener) = self.error_opener.take() {
+                    let _ = opener.focus();
+                }
+                false
+            }
+            Self::Message::DismissSuccess => {
+                self.state.success = false;
+                self.success_timeout = None;
+                true
+            }
+            Self::Message::DismissWarning(index) => {
+                if index < self.state.warnings.len() {
+                    self.state.warnings.remove(index);
+                }
+                true
+            }
+            Self::Message::FormatGrammar => {
+                if let Some(grammar) = format::format_grammar(&self.state.grammar) {
+                    self.state.grammar = grammar;
+                    if let Some(input) = self.grammar_ref.cast::<HtmlTextAreaElement>() {
+                        input.set_value(&self.state.grammar);
+                    }
+                    self.grammar_debounce = None;
+                    self.worker
+                        .send(WorkerInput::LoadGrammar(self.state.grammar.clone()));
+                    let _ = self.state.shared().update_location();
+                }
+                true
+            }
+            Self::Message::ToggleSkipSilent => {
+                if self.state.running {
+                    return false;
+                }
+                self.state.skip_silent = !self.state.skip_silent;
+                let _ = LocalStorage::set(SKIP_SILENT_KEY, self.state.skip_silent);
+                self.sync_breakpoints();
+                true
+            }
+            Self::Message::ToggleNormalizeLineEndings => {
+                if self.state.running {
+                    return false;
+                }
+                self.state.normalize_line_endings = !self.state.normalize_line_endings;
+                let _ = LocalStorage::set(
+                    NORMALIZE_LINE_ENDINGS_KEY,
+                    self.state.normalize_line_endings,
+                );
+                self.worker.send(WorkerInput::SetNormalizeLineEndings(
+                    self.state.normalize_line_endings,
+                ));
+                if self.state.normalize_line_endings {
+                    // the positions of the events are in the normalized input,
+                    // so it's shown in the textarea too
+                    self.state.normalize_inputs();
+                    self.state
+                        .warnings
+                        .retain(|warning| warning != CRLF_WARNING);
+                    self.state.error_pos = None;
+                    self.state.run_selection = None;
+                    self.state.partial_parse = None;
+                    if let Some(input) = self.input_ref.cast::<HtmlTextAreaElement>() {
+                        input.set_value(self.state.input());
+                    }
+                }
+                self.worker
+                    .send(WorkerInput::LoadInput(self.state.input().to_owned()));
+                let _ = self.state.shared().update_location();
+                true
+            }
+            Self::Message::ToggleWhitespace => {
+                self.state.show_whitespace = !self.state.show_whitespace;
+                true
+            }
+            Self::Message::ToggleAstOptimized => {
+                self.state.ast_optimized = !self.state.ast_optimized;
+                self.worker
+                    .send(WorkerInput::RequestAst(self.state.ast_optimized));
+                true
+            }
+            Self::Message::ToggleTheme => {
+                self.state.theme = self.state.theme.toggled();
+                let _ = LocalStorage::set(THEME_KEY, self.state.theme);
+                true
+            }
+            Self::Message::SplitDragStart(e) => {
+                // the divider keeps getting the pointer events while dragged
+                if let Some(divider) = e.target_dyn_into::<Element>() {
+                    let _ = divider.set_pointer_capture(e.pointer_id());
+                }
+                self.split_dragging = true;
+                false
+            }
+            Self::Message::SplitDrag(e) => {
+                let Some(panes) = self.panes_ref.cast::<Element>() else {
+                    return false;
+                };
+                if !self.split_dragging {
+                    return false;
+                }
+                let rect = panes.get_bounding_client_rect();
+                self.state.split_ratio =
+                    ui::split_ratio(e.client_x() as f64, rect.left(), rect.width());
+                true
+            }
+            Self::Message::SplitDragEnd => {
+                self.split_dragging = false;
+                let _ = LocalStorage::set(SPLIT_RATIO_KEY, self.state.split_ratio);
+                false
+            }
+            Self::Message::ToggleMaximized(pane) => {
+                let (maximized, editor, button) = match pane {
+                    Pane::Grammar => (
+                        &mut self.state.grammar_maximized,
+                        &self.grammar_ref,
+                        &self.grammar_maximize_ref,
+                    ),
+                    Pane::Input => (
+                        &mut self.state.input_maximized,
+                        &self.input_ref,
+                        &self.input_maximize_ref,
+                    ),
+                };
+                *maximized = !*maximized;
+                // the editor is focused to type right away,
+                // and the button again once restored not to lose the place
+                self.focus_after_render = Some(if *maximized { editor } else { button }.clone());
+                // only one pane is maximized at a time
+                match pane {
+                    Pane::Grammar => self.state.input_maximized = false,
+                    Pane::Input => self.state.grammar_maximized = false,
+                }
+                true
+            }
+            Self::Message::DragOver(pane, e) => {
+                if self.state.running {
+                    return false;
+                }
+                // allows the drop (instead of the browser opening the file)
+                e.prevent_default();
+                if let Some(transfer) = e.data_transfer() {
+                    transfer.set_drop_effect("copy");
+                }
+                self.drop_target.replace(pane) != Some(pane)
+            }
+            Self::Message::DragLeave => self.drop_target.take().is_some(),
+            Self::Message::DropFile(pane, e) => {
+                e.prevent_default();
+                self.drop_target = None;
+                if self.state.running {
+                    return true;
+                }
+                let Some(file) = e
+                    .data_transfer()
+                    .and_then(|transfer| transfer.files())
+                    .and_then(|files| files.get(0))
+                else {
+                    return true;
+                };
+                let name = file.name();
+                if let Err(e) = check_dropped_file(pane, &name, file.size()) {
+                    self.report_error(e);
+                    return true;
+                }
+                let callback = match pane {
+                    Pane::Grammar => ctx.link().callback(Message::GrammarLoaded),
+                    Pane::Input => ctx
+                        .link()
+                        .callback(move |result| Message::InputLoaded(name.clone(), result)),
+                };
+                if let Err(e) = read_file(&file, callback) {
+                    self.report_error(format!("{:?}", e));
+                }
+                true
+            }
+            Self::Message::RestorePane => {
+                if self.state.grammar_maximized {
+                    Component::update(self, ctx, Message::ToggleMaximized(Pane::Grammar))
+                } else if self.state.input_maximized {
+                    Component::update(self, ctx, Message::ToggleMaximized(Pane::Input))
+                } else {
+                    false
+                }
+            }
+            Self::Message::Escape => {
+                if self.state.grammar_maximized || self.state.input_maximized {
+                    Component::update(self, ctx, Message::RestorePane)
+                } else {
+                    Component::update(self, ctx, Message::Stop)
+                }
+            }
+            Self::Message::SelectLang(e) => {
+                let Some(select) = e.target_dyn_into::<HtmlSelectElement>() else {
+                    return false;
+                };
+                let Some(lang) = Lang::from_code(&select.value()) else {
+                    return false;
+                };
+                self.state.lang = lang;
+                let _ = LocalStorage::set(LANG_KEY, lang);
+                true
+            }
+            Self::Message::TogglePlay => {
+                if self.playback.is_some() {
+                    self.playback = None;
+                } else if self.state.running {
+                    self.start_playback(ctx);
+                }
+                true
+            }
+            Self::Message::SetPlaybackSpeed(e) => {
+                if let Ok(input) = e.target().unwrap().dyn_into::<HtmlInputElement>() {
+                    if let Ok(speed) = input.value().parse() {
+                        self.state.playback_speed = speed;
+                        if self.playback.is_some() {
+                            self.start_playback(ctx);
+                        }
+                    }
+                }
+                true
+            }
+            Self::Message::Reset => {
+                self.grammar_debounce = None;
+                self.input_debounce = None;
+                self.playback = None;
+                // the worker keeps normalizing the line endings
+                self.state = AppState {
+                    theme: self.state.theme,
+                    lang: self.state.lang,
+                    split_ratio: self.state.split_ratio,
+                    indent: self.state.indent,
+                    normalize_line_endings: self.state.normalize_line_endings,
+                    ..AppState::default()
+                };
+                self.worker.send(WorkerInput::Reset);
+                // the worker is left with the default grammar and input
+                self.worker
+                    .send(WorkerInput::LoadGrammar(self.state.grammar.clone()));
+                self.worker
+                    .send(WorkerInput::LoadInput(self.state.input().to_owned()));
+                let _ = self.state.shared().update_location();
+                true
+            }
+            Self::Message::Stop => {
+                self.playback = None;
+                self.state.run_to_end = false;
+                self.state.run_to_rule = None;
+                self.worker.send(WorkerInput::EndSession);
+                self.state.running = false;
+                self.state.events.clear();
+                self.state.cursor = 0;
+                self.state.hits.clear();
+                self.state.attempts.clear();
+                true
+            }
+            Self::Message::SortStats(order) => {
+                self.state.stats_order = order;
+                true
+            }
+        }
+    }
+
+    fn rendered(&mut self, _ctx: &Context<Self>, _first_render: bool) {
+        // only scrolls when the rule changes, so the grammar can still be scrolled by hand
+        let rule = self.state.current_rule().map(str::to_owned);
+        if rule != self.scrolled_rule {
+            if let (Some(view), Some(active)) = (
+                self.grammar_view_ref.cast::<HtmlElement>(),
+                self.active_rule_ref.cast::<HtmlElement>(),
+            ) {
+                view.set_scroll_top(active.offset_top() - view.client_height() / 2);
+            }
+            self.scrolled_rule = rule;
+        }
+        if let Some(element) = self
+            .focus_after_render
+            .take()
+            .and_then(|element| element.cast::<HtmlElement>())
+        {
+            let _ = element.focus();
+        }
+        if self.show_error {
+            self.show_error = false;
+            self.show_error_dialog();
+        }
+        if std::mem::take(&mut self.show_confirm) {
+            if let Some(dialog) = self.confirm_ref.cast::<HtmlDialogElement>() {
+                let _ = dialog.show_modal();
+            }
+        }
+        // the `selected` attributes don't update the selection once the user has changed it
+        if let Some(select) = self.rule_run_ref.cast::<HtmlSelectElement>() {
+            if select.value() != self.state.to_run {
+                select.set_value(&self.state.to_run);
+            }
+        }
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let t = self.state.lang.messages();
+        html! {
+            <>
+                <div id="nescss" lang={self.state.lang.code()} class={classes!(
+                    self.state.theme.class(),
+                    (self.state.grammar_maximized || self.state.input_maximized).then_some("pane-maximized")
+                )}>
+                    {self.header(ctx)}
+                    {self.error_dialog(ctx)}
+                    {self.confirm_dialog(ctx)}
+                    {self.draft(ctx)}
+                    {self.warnings(ctx)}
+                    {self.success(ctx)}
+                    <div class="panes" ref={self.panes_ref.clone()}>
+                    <div class={self.pane_class(Pane::Grammar)} style={self.pane_style(true)}
+                        ondragover={ctx.link().callback(|e| Message::DragOver(Pane::Grammar, e))}
+                        ondragleave={ctx.link().callback(|_| Message::DragLeave)}
+                        ondrop={ctx.link().callback(|e| Message::DropFile(Pane::Grammar, e))}>
+                        {self.maximize_button(ctx, Pane::Grammar)}
+                        <label for="grammar">{t.grammar}</label>
+                        <input type="file" accept=".pest" class="grammar-upload" disabled={self.state.running} onchange={ctx.link().callback(Message::UploadGrammar)} />
+                        <div class="grammar-url">
+                            <input type="url" class="nes-input" placeholder="https://..." disabled={self.state.running}
+                                value={self.state.grammar_url.clone()} oninput={ctx.link().callback(Message::GrammarUrlChange)} />
+                            if self.state.running {
+                                <button type="button" class="nes-btn is-disabled">{t.fetch_grammar}</button>
+                            } else {
+                                <button type="button" class="nes-btn" onclick={ctx.link().callback(|_| Message::FetchGrammar)}>{t.fetch_grammar}</button>
+                            }
+                        </div>
+                        if self.state.running || !self.state.grammar_errors.is_empty() {
+                            <button type="button" class="nes-btn is-disabled grammar-format">{t.format}</button>
+                        } else {
+                            <button type="button" class="nes-btn grammar-format" onclick={ctx.link().callback(|_| Message::FormatGrammar)}>{t.format}</button>
+                        }
+                        if self.state.running || self.state.grammar.is_empty() {
+                            <button type="button" class="nes-btn is-disabled clear-button">{t.clear}</button>
+                        } else {
+                            <button type="button" class="nes-btn is-error clear-button" onclick={ctx.link().callback(|_| Message::AskClear(ClearTarget::Grammar))}>{t.clear}</button>
+                        }
+                        {self.indent_select(ctx)}
+                        {self.grammar_display(ctx)}
+                        {self.grammar_error_marker()}
+                    </div>
+                    <div class="split-divider" role="separator" aria-orientation="vertical" title={t.resize_panes}
+                        onpointerdown={ctx.link().callback(Message::SplitDragStart)}
+                        onpointermove={ctx.link().callback(Message::SplitDrag)}
+                        onpointerup={ctx.link().callback(|_| Message::SplitDragEnd)}
+                        onpointercancel={ctx.link().callback(|_| Message::SplitDragEnd)}></div>
+                    {self.input_display(ctx)}
+                    </div>
+
+                    {self.controls(ctx)}
+                    {self.saved_sessions(ctx)}
+                    {self.rule_diagram()}
+                    {self.event_list(ctx)}
+                    {self.stats(ctx)}
+                    {self.batch_results(ctx)}
+                    {self.parse_tree()}
+                    {self.rule_graph(ctx)}
+                    {self.ast(ctx)}
+                    <br/>
+                    {self.footer(ctx)}
+                </div>
+        </>
+
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::VecDeque;
+
+    use super::{
+        check_dropped_file, default_breakpoints, default_content, versions, AppState, Pane,
+        SAMPLE_GRAMMAR, SAMPLE_INPUT, SAMPLE_RULE,
+    };
+    use crate::debugworker::{DebuggerEvent, RuleModifier};
+
+    /// Returns the default state with the sample grammar
+    /// (whatever the grammar the crate is built with).
+    fn sample() -> AppState {
+        AppState {
+            breakpoints: default_breakpoints(SAMPLE_GRAMMAR),
+            ..AppState::default()
+        }
+    }
+
+    /// Returns a running state with `n` breakpoint events,
+    /// followed by `Eof` if the parsing `succeeded`.
+    fn running(n: usize, succeeded: bool) -> AppState {
+        let mut events = (0..n)
+            .map(|pos| DebuggerEvent::Breakpoint("alpha".to_owned(), pos, None, vec![]))
+            .collect::<VecDeque<_>>();
+        if succeeded {
+            events.push_back(DebuggerEvent::Eof);
+        }
+        AppState {
+            running: true,
+            events,
+            ..AppState::default()
+        }
+    }
+
+    /// Continues until the session ends and returns the number of steps
+    /// and whether the parsing succeeded.
+    fn continue_to_end(state: &mut AppState) -> (usize, bool) {
+        let mut steps = 1;
+        loop {
+            if let Some(success) = state.step_to(state.cursor + 1) {
+                assert!(!state.running);
+                assert!(state.events.is_empty());
+                // the session only ends once
+                assert_eq!(state.step_to(state.cursor + 1), None);
+                return (steps, success);
+            }
+            assert!(state.running);
+            steps += 1;
+        }
+    }
+
+    #[test]
+    fn continue_without_breakpoints() {
+        assert_eq!(continue_to_end(&mut running(0, true)), (1, true));
+    }
+
+    #[test]
+    fn continue_with_one_breakpoint() {
+        assert_eq!(continue_to_end(&mut running(1, true)), (1, true));
+        assert_eq!(continue_to_end(&mut running(1, false)), (1, false));
+    }
+
+    #[test]
+    fn continue_with_breakpoints() {
+        let mut state = running(5, true);
+        assert_eq!(state.step_to(state.cursor + 1), None);
+        assert_eq!(state.cursor, 1);
+        assert_eq!(continue_to_end(&mut state), (4, true));
+        assert_eq!(continue_to_end(&mut running(5, false)), (5, false));
+    }
+
+    #[test]
+    fn continue_without_session() {
+        let mut state = AppState::default();
+        assert_eq!(state.step_to(1), None);
+        assert!(!state.running);
+    }
+
+    #[test]
+    fn backtracked_span_of_failure() {
+        let breakpoint = |rule: &str, pos, stack: &[&str]| {
+            DebuggerEvent::Breakpoint(
+                rule.to_owned(),
+                pos,
+                None,
+                stack.iter().map(|rule| rule.to_string()).collect(),
+            )
+        };
+        let exit =
+            |rule: &str, pos, matched| DebuggerEvent::RuleExit(rule.to_owned(), pos, matched);
+        let mut state = AppState {
+            running: true,
+            events: VecDeque::from([
+                breakpoint("list", 0, &["list"]),
+                breakpoint("ident", 0, &["list", "ident"]),
+                exit("ident", 5, true),
+                breakpoint("ident", 6, &["list", "ident"]),
+                exit("ident", 6, false),
+                exit("list", 0, false),
+            ]),
+            ..AppState::default()
+        };
+        state.cursor = 5;
+        assert_eq!(state.backtracked_span(), Some((0, 6)));
+        // the inner rule failed without advancing
+        state.cursor = 4;
+        assert_eq!(state.backtracked_span(), None);
+        state.cursor = 2;
+        assert_eq!(state.backtracked_span(), None);
+    }
+
+    #[test]
+    fn next_hit_of_rule() {
+        let mut state = AppState {
+            running: true,
+            ..AppState::default()
+        };
+        state.events = [
+            DebuggerEvent::Breakpoint("a".to_owned(), 0, None, vec![]),
+            DebuggerEvent::Breakpoint("b".to_owned(), 0, None, vec![]),
+            DebuggerEvent::Breakpoint("a".to_owned(), 1, None, vec![]),
+            DebuggerEvent::Eof,
+        ]
+        .into();
+        assert_eq!(state.next_hit_of("a"), 2);
+        assert_eq!(state.next_hit_of("b"), 1);
+        // the rule isn't hit again
+        state.cursor = 2;
+        assert_eq!(state.next_hit_of("a"), 4);
+        assert_eq!(state.step_to(state.next_hit_of("a")), Some(true));
+    }
+
+    #[test]
+    fn normalized_line_endings() {
+        let mut state = AppState::default();
+        state.set_input("a\r\nb".to_owned());
+        assert_eq!(state.input(), "a\r\nb");
+        state.normalize_line_endings = true;
+        state.normalize_inputs();
+        assert_eq!(state.input(), "a\nb");
+        state.set_input("c\r\nd\r".to_owned());
+        assert_eq!(state.input(), "c\nd\r");
+    }
+
+    #[test]
+    fn unsaved_draft() {
+        let mut state = AppState::default();
+        assert_eq!(state.unsaved_draft(state.session()), None);
+        let mut draft = state.session();
+        draft.grammar.push_str("\nnew = { \"new\" }");
+        assert_eq!(state.unsaved_draft(draft.clone()), Some(draft.clone()));
+        state.grammar = draft.grammar.clone();
+        assert_eq!(state.unsaved_draft(draft.clone()), None);
+        draft.inputs[0].1.push('x');
+        assert!(state.unsaved_draft(draft).is_some());
+    }
+
+    #[test]
+    fn skip_silent_rules() {
+        let mut state = AppState::default();
+        state
+            .rule_modifiers
+            .insert("ident_list".to_owned(), RuleModifier::Silent);
+        assert!(!state.is_skipped("ident_list"));
+        state.skip_silent = true;
+        assert!(state.is_skipped("ident_list"));
+        assert!(!state.is_skipped("ident"));
+    }
+
+    #[test]
+    fn coverage_of_last_run() {
+        let mut state = sample();
+        assert_eq!(state.coverage(), None);
+        state.attempts.insert("alpha".to_owned(), 10);
+        state.attempts.insert("ident".to_owned(), 2);
+        assert_eq!(state.coverage(), Some((2, 4)));
+    }
+
+    #[test]
+    fn jump_to_event() {
+        let mut state = running(3, true);
+        assert!(state.jump_to(2));
+        assert_eq!(state.cursor, 2);
+        assert!(state.jump_to(0));
+        assert_eq!(state.cursor, 0);
+        // the final `Eof` isn't an event to stop at
+        assert!(!state.jump_to(3));
+        assert!(!state.jump_to(4));
+        assert_eq!(state.cursor, 0);
+        assert!(!AppState::default().jump_to(0));
+    }
+
+    #[test]
+    fn typed_rule_to_run() {
+        let mut state = AppState::default();
+        assert_eq!(state.rule_to_run(), state.to_run);
+        state.typed_rule = " ASCII_DIGIT ".to_owned();
+        assert_eq!(state.rule_to_run(), "ASCII_DIGIT");
+        state.typed_rule = " ".to_owned();
+        assert_eq!(state.rule_to_run(), state.to_run);
+    }
+
+    #[test]
+    fn reachability_root() {
+        let mut state = AppState {
+            to_run: "file".to_owned(),
+            rule_graph: vec![
+                ("file".to_owned(), vec!["item".to_owned()]),
+                ("item".to_owned(), vec![]),
+            ],
+            ..AppState::default()
+        };
+        state.typed_rule = "item".to_owned();
+        assert_eq!(state.reachability_root(), "item");
+        // a partially typed (or built-in) rule doesn't make every rule unreachable
+        state.typed_rule = "ite".to_owned();
+        assert_eq!(state.reachability_root(), "file");
+        state.typed_rule = "ASCII_DIGIT".to_owned();
+        assert_eq!(state.reachability_root(), "file");
+    }
+
+    #[test]
+    fn current_rule() {
+        let mut state = AppState::default();
+        assert_eq!(state.current_rule(), None);
+        state.running = true;
+        state.events = vec![
+            DebuggerEvent::Breakpoint("ident".to_owned(), 0, None, vec![]),
+            DebuggerEvent::RuleExit("ident".to_owned(), 5, true),
+            DebuggerEvent::Eof,
+        ]
+        .into();
+        assert_eq!(state.current_rule(), Some("ident"));
+        state.cursor = 1;
+        assert_eq!(state.current_rule(), Some("ident"));
+        state.cursor = 2;
+        assert_eq!(state.current_rule(), None);
+    }
+
+    #[test]
+    fn first_set_of_clicked_rule() {
+        let mut state = AppState::default();
+        state.rule_analysis.first = vec![("digit".to_owned(), vec!["'0'..'9'".to_owned()])];
+        assert_eq!(state.first_set(), None);
+        state.first_set_rule = Some("digit".to_owned());
+        assert_eq!(
+            state.first_set().as_deref(),
+            Some("FIRST(digit) = {'0'..'9'}")
+        );
+        state.first_set_rule = Some("unknown".to_owned());
+        assert_eq!(state.first_set(), None);
+    }
+
+    #[test]
+    fn position_summary() {
+        let mut state = AppState::default();
+        state.set_input("ab\ncd".to_owned());
+        assert_eq!(state.position_summary(), None);
+        state.running = true;
+        state.events = vec![
+            DebuggerEvent::Breakpoint("alpha".to_owned(), 4, None, vec![]),
+            DebuggerEvent::RuleExit("alpha".to_owned(), 5, true),
+        ]
+        .into();
+        assert_eq!(
+            state.position_summary().as_deref(),
+            Some("stopped at `alpha` @ line 2 col 2")
+        );
+        state.cursor = 1;
+        assert_eq!(
+            state.position_summary().as_deref(),
+            Some("matched `alpha` @ line 2 col 3")
+        );
+    }
+
+    #[test]
+    fn select_rule_to_run_by_name() {
+        let mut state = sample();
+        // the selection doesn't depend on the order of the breakpoints
+        state.breakpoints.reverse();
+        assert!(state.select_rule_to_run("digit"));
+        assert_eq!(state.to_run, "digit");
+        assert!(!state.select_rule_to_run("unknown"));
+        assert_eq!(state.to_run, "digit");
+    }
+
+    #[test]
+    fn locked_pest_versions() {
+        let versions = versions();
+        assert!(versions.starts_with(&format!("pest-web-debug {}", env!("CARGO_PKG_VERSION"))));
+        assert!(!versions.contains("unknown"), "{}", versions);
+    }
+
+    #[test]
+    fn trace_log() {
+        let mut state = AppState::default();
+        state.set_input("ab\ncd".to_owned());
+        state.events = vec![
+            DebuggerEvent::Breakpoint("alpha".to_owned(), 3, None, vec![]),
+            DebuggerEvent::RuleExit("alpha".to_owned(), 4, true),
+            DebuggerEvent::Eof,
+        ]
+        .into();
+        assert_eq!(
+            state.trace_log(),
+            "1. 2:1 breakpoint alpha @ 3\n\
+             2. 2:2 exit alpha @ 4 (matched)\n\
+             3. eof\n"
+        );
+        state.events.pop_back();
+        state.error = Some("expected digit".to_owned());
+        state.error_pos = Some(4);
+        assert!(state
+            .trace_log()
+            .ends_with("3. 2:2 error @ 4: expected digit\n"));
+    }
+
+    #[test]
+    fn dropped_files() {
+        assert_eq!(
+            check_dropped_file(Pane::Grammar, "json.PEST", 100.0),
+            Ok(())
+        );
+        assert_eq!(
+            check_dropped_file(Pane::Grammar, "input.json", 100.0),
+            Err(
+                "input.json isn't a grammar: only .pest files can be dropped on the grammar"
+                    .to_owned()
+            )
+        );
+        assert_eq!(check_dropped_file(Pane::Input, "input.json", 100.0), Ok(()));
+        assert_eq!(
+            check_dropped_file(Pane::Input, "huge.txt", 20.0 * 1024.0 * 1024.0),
+            Err("huge.txt is too large to load (20.0 MiB, the limit is 16 MiB)".to_owned())
+        );
+    }
+
+    #[test]
+    fn build_time_defaults() {
+        let (grammar, input, rule) = default_content(None, None, None);
+        assert_eq!(
+            (grammar.as_str(), input.as_str(), rule.as_str()),
+            (SAMPLE_GRAMMAR, SAMPLE_INPUT, SAMPLE_RULE)
+        );
+        let grammar = "json = { value }\nvalue = { \"null\" }";
+        let (_, input, rule) = default_content(Some(grammar), None, None);
+        assert_eq!((input.as_str(), rule.as_str()), (SAMPLE_INPUT, "json"));
+        let (_, input, rule) = default_content(Some(grammar), Some("null"), Some("value"));
+        assert_eq!((input.as_str(), rule.as_str()), ("null", "value"));
+        let breakpoints = |grammar| {
+            default_breakpoints(grammar)
+                .into_iter()
+                .map(|breakpoint| breakpoint.rule)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            breakpoints(SAMPLE_GRAMMAR),
+            ["alpha", "digit", "ident", "ident_list"]
+        );
+        assert_eq!(breakpoints(grammar), ["json", "value"]);
+    }
+}
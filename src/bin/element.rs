@@ -0,0 +1,3 @@
+fn main() {
+    pest_web_debug::define_element("pest-debugger");
+}
@@ -2,5 +2,9 @@ use pest_web_debug::Worker;
 use yew_agent::PublicWorker;
 
 fn main() {
+    // the panics that aren't caught (and abort the worker) are at least logged
+    std::panic::set_hook(Box::new(|info| {
+        web_sys::console::error_1(&info.to_string().into());
+    }));
     Worker::register();
 }
@@ -1,47 +1,512 @@
 use std::{
-    collections::HashSet,
-    sync::{Arc, Mutex},
+    any::Any,
+    cell::RefCell,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, PoisonError,
+    },
 };
 
-use pest_meta::{optimizer::OptimizedRule, parse_and_optimize, parser::rename_meta_rule};
+use pest::{
+    error::{ErrorVariant, InputLocation, LineColLocation},
+    iterators::Pair,
+};
+use pest_meta::{
+    ast::{Rule, RuleType},
+    optimizer::{self, OptimizedRule},
+    parser::{self, rename_meta_rule},
+    validator,
+};
 use pest_vm::Vm;
 use serde::{Deserialize, Serialize};
 
+use crate::{
+    analysis,
+    diagram::{self, RuleDiagram},
+    trace::{self, Breakpoints, Trace, ROOT_RULE},
+};
+
+#[cfg(feature = "web")]
 use yew_agent::{HandlerId, Public, WorkerLink};
+
+/// A function the events are streamed to while parsing.
+type Sink = Box<dyn Fn(Vec<DebuggerEvent>)>;
+
+thread_local! {
+    /// Where the events of the parsing are streamed to, if anywhere (see `streaming`).
+    /// The parser listener has to be `Send` and `Sync`, which e.g. `WorkerLink` isn't,
+    /// so it's reached from here rather than captured.
+    static STREAM: RefCell<Option<Sink>> = const { RefCell::new(None) };
+}
+
+/// Calls `f` with the breakpoint events passed to `sink` as they are hit while parsing.
+/// The events returned by the parsing then only correct the streamed events' positions
+/// (see `DebuggerEvent::Correction`), instead of including them.
+#[cfg(any(feature = "web", test))]
+fn streaming<T>(sink: impl Fn(Vec<DebuggerEvent>) + 'static, f: impl FnOnce() -> T) -> T {
+    STREAM.with(|stream| *stream.borrow_mut() = Some(Box::new(sink)));
+    let result = f();
+    STREAM.with(|stream| *stream.borrow_mut() = None);
+    result
+}
+
+/// Whether the events are streamed while parsing.
+fn is_streaming() -> bool {
+    STREAM.with(|stream| stream.borrow().is_some())
+}
+
+/// Passes the events to the sink of `streaming`, if any.
+fn stream(events: Vec<DebuggerEvent>) {
+    STREAM.with(|stream| {
+        if let Some(sink) = stream.borrow().as_ref() {
+            sink(events);
+        }
+    });
+}
+
 /// Events that are sent from the debugger.
-#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
 pub enum DebuggerEvent {
     /// A breakpoint encountered.
     /// The first element is the rule name.
     /// The second element is the position.
-    Breakpoint(String, usize),
+    /// The third element is the end position of the rule's match,
+    /// if it's known (i.e. the match ended up in the parse result).
+    /// The fourth element is the call stack of rules (outermost first),
+    /// ending with the rule itself.
+    Breakpoint(String, usize, Option<usize>, Vec<String>),
+    /// A rule attempted at a breakpoint was exited.
+    /// The first element is the rule name.
+    /// The second element is the position where the rule's match ended
+    /// (or where it was attempted, if it failed).
+    /// The third element is whether the rule matched.
+    RuleExit(String, usize, bool),
+    /// A position of a `Breakpoint` or `RuleExit` event that was streamed while parsing,
+    /// corrected from the parse result once the parsing is done.
+    /// The first element is the index of the event among the `Breakpoint`
+    /// and `RuleExit` events of the run.
+    /// The second element is the end position of the `Breakpoint`'s match,
+    /// or the position of the `RuleExit`.
+    Correction(usize, usize),
     /// The end of the input has been reached.
     Eof,
     /// A parsing error encountered.
-    Error(String),
-    /// Grammar rule names
-    Rules(Vec<String>),
+    /// The second element is the input position where parsing failed,
+    /// if the error comes from parsing the input.
+    Error(String, Option<usize>),
+    /// The rules that were expected (positives) and unexpected (negatives)
+    /// where parsing failed. It's sent before the `Error` of the input,
+    /// unless the error has no such details.
+    Expected(Vec<String>, Vec<String>),
+    /// Grammar rule names with their modifiers
+    Rules(Vec<(String, RuleModifier)>),
+    /// The errors encountered while parsing the grammar.
+    GrammarErrors(Vec<GrammarError>),
+    /// A possible problem with the (successfully parsed) grammar.
+    Warning(String),
+    /// The rules referenced by each grammar rule.
+    RuleGraph(Vec<(String, Vec<String>)>),
+    /// The results of the static analysis of the grammar rules.
+    RuleAnalysis(RuleAnalysis),
+    /// The railroad diagrams of the grammar rules.
+    RuleDiagrams(Vec<(String, RuleDiagram)>),
+    /// The pretty-printed rules of the grammar AST (raw or optimized).
+    Ast(String),
+    /// The parse result of a successful parse,
+    /// serialized as a JSON list of `ParseTreeNode`s.
+    ParseTreeJson(String),
+    /// The parsing succeeded, but didn't consume the whole input.
+    /// The element is the position where the matching stopped.
+    PartialParse(usize),
+    /// The time (in milliseconds) the parsing took.
+    Timing(f64),
+    /// The parsing was aborted after the step limit (i.e. rule attempts) was reached.
+    /// The element is the number of steps.
+    Aborted(usize),
+    /// Not all breakpoints were collected, as there were more than the event limit.
+    /// The element is the total number of breakpoints hit.
+    Truncated(usize),
+    /// The outcome of parsing one of the inputs of a batch run.
+    /// The first element is the index of the input in the batch.
+    /// The second element is the error message, if the parsing failed.
+    BatchResult(usize, Option<String>),
+}
+
+impl std::fmt::Display for DebuggerEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DebuggerEvent::Breakpoint(rule, pos, _, _) => {
+                write!(f, "breakpoint {} @ {}", rule, pos)
+            }
+            DebuggerEvent::RuleExit(rule, pos, true) => {
+                write!(f, "exit {} @ {} (matched)", rule, pos)
+            }
+            DebuggerEvent::RuleExit(rule, pos, false) => {
+                write!(f, "exit {} @ {} (failed)", rule, pos)
+            }
+            DebuggerEvent::Correction(index, pos) => {
+                write!(f, "correction of event {} @ {}", index, pos)
+            }
+            DebuggerEvent::Eof => write!(f, "eof"),
+            DebuggerEvent::Error(error, Some(pos)) => write!(f, "error @ {}: {}", pos, error),
+            DebuggerEvent::Error(error, None) => write!(f, "error: {}", error),
+            DebuggerEvent::Expected(positives, negatives) => write!(
+                f,
+                "expected: [{}], unexpected: [{}]",
+                positives.join(", "),
+                negatives.join(", ")
+            ),
+            DebuggerEvent::Rules(rules) => {
+                let rules = rules
+                    .iter()
+                    .map(|(rule, modifier)| match modifier {
+                        RuleModifier::Normal => rule.clone(),
+                        _ => format!("{} ({})", rule, modifier.symbol()),
+                    })
+                    .collect::<Vec<_>>();
+                write!(f, "rules: [{}]", rules.join(", "))
+            }
+            DebuggerEvent::GrammarErrors(errors) => {
+                write!(f, "grammar errors:")?;
+                for error in errors {
+                    write!(f, "\n{}:{}: {}", error.line, error.col, error.message)?;
+                }
+                Ok(())
+            }
+            DebuggerEvent::Warning(warning) => write!(f, "warning: {}", warning),
+            DebuggerEvent::RuleGraph(graph) => {
+                write!(f, "rule graph:")?;
+                for (rule, callees) in graph {
+                    write!(f, "\n{} -> [{}]", rule, callees.join(", "))?;
+                }
+                Ok(())
+            }
+            DebuggerEvent::RuleAnalysis(analysis) => {
+                write!(f, "nullable rules: [{}]", analysis.nullable.join(", "))
+            }
+            DebuggerEvent::RuleDiagrams(diagrams) => {
+                let rules = diagrams
+                    .iter()
+                    .map(|(rule, _)| rule.as_str())
+                    .collect::<Vec<_>>();
+                write!(f, "rule diagrams: [{}]", rules.join(", "))
+            }
+            DebuggerEvent::Ast(ast) => write!(f, "ast:\n{}", ast),
+            DebuggerEvent::ParseTreeJson(tree) => write!(f, "parse tree: {}", tree),
+            DebuggerEvent::PartialParse(pos) => write!(f, "partial parse until {}", pos),
+            DebuggerEvent::Timing(time) => write!(f, "parsed in {:.1} ms", time),
+            DebuggerEvent::Aborted(steps) => write!(f, "aborted after {} steps", steps),
+            DebuggerEvent::Truncated(total) => write!(f, "truncated: {} breakpoints hit", total),
+            DebuggerEvent::BatchResult(index, None) => write!(f, "input {}: ok", index),
+            DebuggerEvent::BatchResult(index, Some(error)) => {
+                write!(f, "input {}: {}", index, error)
+            }
+        }
+    }
+}
+
+/// The modifier of a grammar rule, e.g. `_` in `rule = _{ ... }`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum RuleModifier {
+    /// no modifier
+    #[default]
+    Normal,
+    /// `_`
+    Silent,
+    /// `@`
+    Atomic,
+    /// `$`
+    CompoundAtomic,
+    /// `!`
+    NonAtomic,
+}
+
+impl From<RuleType> for RuleModifier {
+    fn from(ty: RuleType) -> Self {
+        match ty {
+            RuleType::Normal => RuleModifier::Normal,
+            RuleType::Silent => RuleModifier::Silent,
+            RuleType::Atomic => RuleModifier::Atomic,
+            RuleType::CompoundAtomic => RuleModifier::CompoundAtomic,
+            RuleType::NonAtomic => RuleModifier::NonAtomic,
+        }
+    }
+}
+
+impl RuleModifier {
+    /// The symbol of the modifier in the grammar (empty for normal rules).
+    pub fn symbol(self) -> &'static str {
+        match self {
+            RuleModifier::Normal => "",
+            RuleModifier::Silent => "_",
+            RuleModifier::Atomic => "@",
+            RuleModifier::CompoundAtomic => "$",
+            RuleModifier::NonAtomic => "!",
+        }
+    }
+
+    /// Describes how the modifier changes the rule.
+    pub fn description(self) -> &'static str {
+        match self {
+            RuleModifier::Normal => "normal rule",
+            RuleModifier::Silent => "silent: it produces no pairs",
+            RuleModifier::Atomic => {
+                "atomic: no implicit whitespace, and the rules it calls produce no pairs"
+            }
+            RuleModifier::CompoundAtomic => {
+                "compound atomic: no implicit whitespace, but the rules it calls produce pairs"
+            }
+            RuleModifier::NonAtomic => "non-atomic: it cancels the effect of atomic rules",
+        }
+    }
+}
+
+/// The results of the static analysis of the grammar rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RuleAnalysis {
+    /// the rules that can match without consuming any input, in the grammar order
+    pub nullable: Vec<String>,
+    /// the FIRST set of each rule (what its matches can begin with), in the grammar order
+    #[serde(default)]
+    pub first: Vec<(String, Vec<String>)>,
+}
+
+/// An error in the grammar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GrammarError {
+    /// the 1-based line of the error
+    pub line: usize,
+    /// the 1-based column of the error
+    pub col: usize,
+    /// the formatted error message
+    pub message: String,
+}
+
+impl GrammarError {
+    /// Formats the errors as a single message.
+    pub fn summary(errors: &[GrammarError]) -> String {
+        format!(
+            "error parsing\n\n{}",
+            errors
+                .iter()
+                .map(|error| error.message.as_str())
+                .collect::<Vec<_>>()
+                .join("\n")
+        )
+    }
+}
+
+/// A node of the parse result.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ParseTreeNode {
+    /// the rule name
+    pub rule: String,
+    /// the start position of the matched span
+    pub start: usize,
+    /// the end position of the matched span
+    pub end: usize,
+    /// the matched text
+    pub text: String,
+    /// the inner pairs
+    pub children: Vec<ParseTreeNode>,
+}
+
+impl From<Pair<'_, &str>> for ParseTreeNode {
+    fn from(pair: Pair<'_, &str>) -> Self {
+        let span = pair.as_span();
+        Self {
+            rule: pair.as_rule().to_owned(),
+            start: span.start(),
+            end: span.end(),
+            text: span.as_str().to_owned(),
+            children: pair.into_inner().map(ParseTreeNode::from).collect(),
+        }
+    }
 }
 
+/// Returns how long (in milliseconds) running `f` took, along with its result.
+/// In the browser, the global `performance` object is used,
+/// which is available both in windows and in workers.
+#[cfg(all(feature = "web", target_arch = "wasm32"))]
+fn timed<T>(f: impl FnOnce() -> T) -> (T, f64) {
+    use wasm_bindgen::JsCast;
+
+    let now = || {
+        js_sys::Reflect::get(&js_sys::global(), &"performance".into())
+            .ok()
+            .and_then(|performance| performance.dyn_into::<web_sys::Performance>().ok())
+            .map_or_else(js_sys::Date::now, |performance| performance.now())
+    };
+    let start = now();
+    let result = f();
+    (result, now() - start)
+}
+
+/// Returns how long (in milliseconds) running `f` took, along with its result.
+#[cfg(not(all(feature = "web", target_arch = "wasm32")))]
+fn timed<T>(f: impl FnOnce() -> T) -> (T, f64) {
+    let start = std::time::Instant::now();
+    let result = f();
+    (result, start.elapsed().as_secs_f64() * 1000.0)
+}
+
+/// Returns the message of a caught panic.
+fn panic_message(panic: &(dyn Any + Send)) -> &str {
+    panic
+        .downcast_ref::<&str>()
+        .copied()
+        .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+        .unwrap_or("unknown panic")
+}
+
+/// The rules of a grammar before and after the optimization.
+type GrammarAsts = (Vec<Rule>, Vec<OptimizedRule>);
+
+/// The warning about an input with CRLF line endings.
+pub const CRLF_WARNING: &str =
+    "the input has CRLF (\\r\\n) line endings, which rules expecting \"\\n\" don't match; \
+    normalize the line endings if they aren't expected";
+
+/// Converts the CRLF (`\r\n`) line endings of the input to LF (`\n`).
+pub fn normalize_line_endings(input: &str) -> String {
+    input.replace("\r\n", "\n")
+}
+
+/// The default maximum number of breakpoint events collected in a debugger session.
+pub const DEFAULT_EVENT_LIMIT: usize = 10_000;
+
+/// The default maximum number of rule attempts in a debugger session.
+pub const DEFAULT_STEP_LIMIT: usize = 1_000_000;
+
 /// Debugger for pest grammars.
-#[derive(Default)]
 pub struct DebuggerContext {
     grammar: Option<Vec<OptimizedRule>>,
+    /// the rules of the loaded grammar before the optimization
+    raw_grammar: Option<Vec<Rule>>,
+    /// the source of the loaded grammar,
+    /// so that it isn't re-parsed when it's loaded again unchanged
+    grammar_source: Option<String>,
+    /// the possible problems found in the loaded grammar
+    warnings: Vec<String>,
+    /// the rules referenced by each rule of the loaded grammar
+    rule_graph: Vec<(String, Vec<String>)>,
+    /// the static analysis of the rules of the loaded grammar
+    rule_analysis: RuleAnalysis,
+    /// the railroad diagrams of the rules of the loaded grammar
+    rule_diagrams: Vec<(String, RuleDiagram)>,
+    /// the rule names of the loaded grammar with their modifiers, in the source order
+    rules: Vec<(String, RuleModifier)>,
     input: Option<String>,
-    breakpoints: HashSet<String>,
+    breakpoints: Breakpoints,
+    /// the maximum number of breakpoint events collected in a session
+    event_limit: usize,
+    /// the maximum number of rule attempts in a session
+    step_limit: usize,
+    /// whether the CRLF line endings of the loaded input are converted to LF
+    normalize_line_endings: bool,
+    /// the number of changes of the grammar, input or session so far,
+    /// which the events of a run are tagged with, so that outdated ones can be dropped;
+    /// a running parse is aborted once it moves on (it's shared through `epoch_counter`,
+    /// as the worker itself only handles the changes once the parsing is done)
+    epoch: Arc<AtomicUsize>,
+}
+
+impl Default for DebuggerContext {
+    fn default() -> Self {
+        Self {
+            grammar: None,
+            raw_grammar: None,
+            grammar_source: None,
+            warnings: vec![],
+            rule_graph: vec![],
+            rule_analysis: RuleAnalysis::default(),
+            rule_diagrams: vec![],
+            rules: vec![],
+            input: None,
+            breakpoints: Breakpoints::default(),
+            event_limit: DEFAULT_EVENT_LIMIT,
+            step_limit: DEFAULT_STEP_LIMIT,
+            normalize_line_endings: false,
+            epoch: Arc::new(AtomicUsize::new(0)),
+        }
+    }
 }
 
 impl DebuggerContext {
     /// Loads a grammar from a string.
-    pub fn load_grammar_direct(&mut self, grammar: &str) -> Result<(), String> {
-        self.grammar = Some(DebuggerContext::parse_grammar(grammar)?);
+    /// It's a no-op if the same grammar is already loaded.
+    pub fn load_grammar_direct(&mut self, grammar: &str) -> Result<(), Vec<GrammarError>> {
+        if self.grammar.is_some() && self.grammar_source.as_deref() == Some(grammar) {
+            return Ok(());
+        }
+        let (raw_ast, ast) = DebuggerContext::parse_grammar(grammar)?;
+        self.warnings.clear();
+        // e.g. only whitespace or comments, which leaves nothing to run
+        if ast.is_empty() {
+            self.warnings.push("grammar is empty".to_owned());
+        }
+        let left_recursive = analysis::left_recursive_rules(&ast);
+        if !left_recursive.is_empty() {
+            self.warnings.push(format!(
+                "left-recursive rules (they can call themselves after a stack operation \
+                 without consuming any input, and then never terminate): {}",
+                left_recursive.join(", ")
+            ));
+        }
+        let unsupported = analysis::vm_unsupported_rules(&ast);
+        if !unsupported.is_empty() {
+            self.warnings.push(format!(
+                "rules the debugger runs as the pest built-ins of the same names, not as defined \
+                 (unlike pest_derive, pest_vm doesn't let the grammar override them): {}",
+                unsupported.join(", ")
+            ));
+        }
+        self.rule_graph = analysis::rule_graph(&ast);
+        self.rule_analysis = RuleAnalysis {
+            nullable: analysis::nullable_rules(&ast),
+            first: analysis::first_sets(&ast),
+        };
+        self.rule_diagrams = diagram::rule_diagrams(&ast);
+        let unreferenced = analysis::unreferenced_rules(&self.rule_graph);
+        if unreferenced.len() > 1 {
+            self.warnings.push(format!(
+                "rules that aren't referenced by any other rule (only one is usually the entry rule): {}",
+                unreferenced.join(", ")
+            ));
+        }
+        self.rules = analysis::rules(grammar);
+        self.grammar = Some(ast);
+        self.raw_grammar = Some(raw_ast);
+        self.grammar_source = Some(grammar.to_owned());
 
         Ok(())
     }
 
     /// Loads a parsing input from a string.
+    /// Its line endings are normalized if `set_normalize_line_endings` is on.
     pub fn load_input_direct(&mut self, input: String) {
-        self.input = Some(input);
+        self.input = Some(if self.normalize_line_endings {
+            normalize_line_endings(&input)
+        } else {
+            input
+        });
+    }
+
+    /// Sets whether the CRLF line endings of the inputs loaded from now on are converted to LF.
+    /// (The positions of the debugger events are in the normalized input.)
+    pub fn set_normalize_line_endings(&mut self, normalize: bool) {
+        self.normalize_line_endings = normalize;
+    }
+
+    /// Returns a warning about the loaded input, i.e. the `CRLF_WARNING`
+    /// if it has CRLF line endings (the grammar may only expect `\n`).
+    pub fn input_warning(&self) -> Option<String> {
+        self.input
+            .as_deref()
+            .filter(|input| input.contains("\r\n"))
+            .map(|_| CRLF_WARNING.to_owned())
     }
 
     /// Adds all grammar rules as breakpoints.
@@ -53,7 +518,7 @@ impl DebuggerContext {
             .as_ref()
             .ok_or("DebuggerError::GrammarNotOpened".to_string())?;
         for rule in ast {
-            self.breakpoints.insert(rule.name.clone());
+            self.breakpoints.rules.insert(rule.name.clone());
         }
 
         Ok(())
@@ -61,107 +526,447 @@ impl DebuggerContext {
 
     /// Adds a rule to breakpoints.
     pub fn add_breakpoint(&mut self, rule: String) {
-        self.breakpoints.insert(rule);
+        self.breakpoints.conditions.remove(&rule);
+        self.breakpoints.rules.insert(rule);
+    }
+
+    /// Adds a rule to breakpoints that is only hit
+    /// when the rule is attempted at a position within `start..=end`.
+    pub fn add_conditional_breakpoint(&mut self, rule: String, start: usize, end: usize) {
+        self.breakpoints
+            .conditions
+            .insert(rule.clone(), (start, end));
+        self.breakpoints.rules.insert(rule);
+    }
+
+    /// Sets the number of hits of a breakpoint to ignore
+    /// before the debugger stops at it.
+    pub fn set_breakpoint_ignore(&mut self, rule: String, ignore: usize) {
+        if ignore == 0 {
+            self.breakpoints.ignores.remove(&rule);
+        } else {
+            self.breakpoints.ignores.insert(rule, ignore);
+        }
     }
 
     /// Removes a rule from breakpoints.
     pub fn delete_breakpoint(&mut self, rule: &str) {
-        self.breakpoints.remove(rule);
+        self.breakpoints.rules.remove(rule);
+        self.breakpoints.conditions.remove(rule);
+    }
+
+    /// Sets the maximum number of breakpoint events collected in a session.
+    pub fn set_event_limit(&mut self, limit: usize) {
+        self.event_limit = limit;
+    }
+
+    /// Sets the maximum number of rule attempts in a session,
+    /// after which parsing is aborted.
+    pub fn set_step_limit(&mut self, limit: usize) {
+        self.step_limit = limit;
+    }
+
+    /// Returns the current epoch (see `WorkerInput::bumps_epoch`).
+    pub fn epoch(&self) -> usize {
+        self.epoch.load(Ordering::SeqCst)
+    }
+
+    /// Starts a new epoch, which outdates the events of the previous runs
+    /// and aborts the running parse, if any.
+    pub fn bump_epoch(&mut self) {
+        self.epoch.fetch_add(1, Ordering::SeqCst);
+    }
+
+    /// Returns the counter of the epochs, so that the running parse
+    /// can be aborted from elsewhere by incrementing it.
+    pub fn epoch_counter(&self) -> Arc<AtomicUsize> {
+        self.epoch.clone()
+    }
+
+    /// Clears the grammar, input and breakpoints
+    /// (but keeps the limits, the line endings option and the epoch).
+    pub fn reset(&mut self) {
+        *self = Self {
+            event_limit: self.event_limit,
+            step_limit: self.step_limit,
+            normalize_line_endings: self.normalize_line_endings,
+            epoch: self.epoch.clone(),
+            ..Self::default()
+        };
     }
 
     /// Removes all breakpoints.
     pub fn delete_all_breakpoints(&mut self) {
-        self.breakpoints.clear();
+        self.breakpoints.rules.clear();
+        self.breakpoints.conditions.clear();
     }
 
-    fn handle(
+    /// Parses the input with a rule and collects the debugger events
+    /// of the breakpoints hit at or after the `from_pos` position.
+    fn collect(
         &self,
         ast: Vec<OptimizedRule>,
-        rule: String,
-        input: String,
-        rsender: WorkerLink<Worker>,
-        handler_id: HandlerId,
-    ) {
-        let breakpoints = self.breakpoints.clone();
-        // FIXME: this is currently unnecessary, unless
-        // there's a way to spawn a thread in WASM
-        // that can be paused/resumed.
-        let events = Arc::new(Mutex::new(vec![]));
-        let events2 = events.clone();
+        rule: &str,
+        input: &str,
+        from_pos: usize,
+    ) -> Vec<DebuggerEvent> {
+        // in a debugger session, the breakpoints are sent as they are hit,
+        // and their end positions are corrected once the parsing is done
+        let streaming = is_streaming();
+        let trace = Arc::new(Mutex::new(Trace::new(
+            Breakpoints {
+                from_pos,
+                ..self.breakpoints.clone()
+            },
+            &ast,
+            self.event_limit,
+            self.step_limit,
+        )));
+        let trace2 = trace.clone();
+        let (epoch, started) = (self.epoch.clone(), self.epoch());
         let vm = Vm::new_with_listener(
-            ast,
+            trace::wrap(trace::instrument(ast), rule),
             Box::new(move |rule, pos| {
-                if breakpoints.contains(&rule) {
-                    // FIXME: limit the size of events?
-                    events2
-                        .lock()
-                        .unwrap()
-                        .push(DebuggerEvent::Breakpoint(rule, pos.pos()));
+                // the parse is outdated by a change of the grammar, input or session
+                if epoch.load(Ordering::SeqCst) != started {
+                    return true;
+                }
+                let mut trace = trace2.lock().unwrap_or_else(PoisonError::into_inner);
+                let terminate = trace.on_attempt(rule, pos.pos());
+                if streaming {
+                    stream(trace.take_new());
                 }
-                false
+                terminate
             }),
         );
-        let rrsender = rsender.clone();
-        let send_events = move || {
-            let events = events.lock().unwrap();
-            for event in events.iter() {
-                rrsender.respond(handler_id, event.clone());
+        let mut events = vec![];
+        let finish_trace = |events: &mut Vec<DebuggerEvent>,
+                            spans: &HashMap<(&str, usize), usize>| {
+            // the lock is poisoned if the parser panicked in the listener
+            let mut trace = trace.lock().unwrap_or_else(PoisonError::into_inner);
+            let taken = trace.taken();
+            let finished = trace.finish(spans);
+            // the streamed events are only corrected
+            events.extend(
+                trace
+                    .corrections()
+                    .iter()
+                    .filter(|(index, _)| *index < taken)
+                    .map(|(index, pos)| DebuggerEvent::Correction(*index, *pos)),
+            );
+            events.extend(finished.into_iter().skip(taken));
+            if let Some(total) = trace.truncated() {
+                events.push(DebuggerEvent::Truncated(total));
             }
         };
-        match vm.parse(&rule, &input) {
-            Ok(_) => {
-                send_events();
-                rsender.respond(handler_id, DebuggerEvent::Eof)
+        // some grammars make the parser panic (e.g. `POP` on an empty stack),
+        // which would otherwise kill the worker
+        let (result, time) =
+            timed(|| panic::catch_unwind(AssertUnwindSafe(|| vm.parse(ROOT_RULE, input))));
+        if self.epoch() != started {
+            // the events are outdated (and dropped anyway)
+            return vec![];
+        }
+        events.push(DebuggerEvent::Timing(time));
+        let aborted = trace
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .aborted();
+        if let Some(steps) = aborted {
+            // the result of an aborted parsing is meaningless
+            finish_trace(&mut events, &HashMap::new());
+            events.push(DebuggerEvent::Aborted(steps));
+            return events;
+        }
+        let result = match result {
+            Ok(result) => result,
+            Err(panic) => {
+                finish_trace(&mut events, &HashMap::new());
+                let message = panic_message(panic.as_ref());
+                // e.g. a mistyped start rule
+                let error = if message == format!("undefined rule {}", rule) {
+                    format!(
+                        "the rule {} isn't defined in the grammar (nor built into pest)",
+                        rule
+                    )
+                } else {
+                    format!("internal parser panic: {}", message)
+                };
+                events.push(DebuggerEvent::Error(error, None));
+                return events;
+            }
+        };
+        match result {
+            Ok(mut pairs) => {
+                // the start rule matched what the root rule did (see `trace::wrap`)
+                let (end, pairs) = match pairs.next() {
+                    Some(root) => (root.as_span().end(), root.into_inner()),
+                    None => (0, pairs),
+                };
+                // the listener is only called when a rule is attempted,
+                // so the end positions are filled in from the parse result
+                let spans: HashMap<(&str, usize), usize> = pairs
+                    .clone()
+                    .flatten()
+                    .map(|pair| {
+                        let span = pair.as_span();
+                        ((pair.as_rule(), span.start()), span.end())
+                    })
+                    .collect();
+                finish_trace(&mut events, &spans);
+                // a rule without `EOI` may successfully match only a prefix of the input
+                if end < input.len() {
+                    events.push(DebuggerEvent::PartialParse(end));
+                }
+                let tree = pairs.map(ParseTreeNode::from).collect::<Vec<_>>();
+                events.push(match serde_json::to_string(&tree) {
+                    Ok(tree) => DebuggerEvent::ParseTreeJson(tree),
+                    Err(error) => DebuggerEvent::Error(error.to_string(), None),
+                });
+                events.push(DebuggerEvent::Eof);
             }
             Err(error) => {
-                send_events();
-                rsender.respond(handler_id, DebuggerEvent::Error(error.to_string()))
+                finish_trace(&mut events, &HashMap::new());
+                let pos = match error.location {
+                    InputLocation::Pos(pos) => pos,
+                    InputLocation::Span((start, _)) => start,
+                };
+                // e.g. a silent start rule failing is reported as the root rule
+                let rename = |name: &&str| {
+                    if *name == ROOT_RULE {
+                        rule.to_owned()
+                    } else {
+                        name.to_string()
+                    }
+                };
+                if let ErrorVariant::ParsingError {
+                    positives,
+                    negatives,
+                } = &error.variant
+                {
+                    events.push(DebuggerEvent::Expected(
+                        positives.iter().map(rename).collect(),
+                        negatives.iter().map(rename).collect(),
+                    ));
+                }
+                // (quoted like the rules of the error without renaming)
+                let error = error.renamed_rules(|name| format!("{:?}", rename(name)));
+                events.push(DebuggerEvent::Error(error.to_string(), Some(pos)));
             }
         };
+        events
     }
 
-    fn parse_grammar(grammar: &str) -> Result<Vec<OptimizedRule>, String> {
-        match parse_and_optimize(grammar) {
-            Ok((_, ast)) => Ok(ast),
+    /// Pretty-prints the rules of the loaded grammar, one per line,
+    /// either as written (`optimized == false`) or as optimized by pest.
+    /// It returns an error if the grammar hasn't been loaded yet.
+    pub fn ast_dump(&self, optimized: bool) -> Result<String, String> {
+        let rules = if optimized {
+            self.grammar.as_ref().map(|ast| {
+                ast.iter()
+                    .map(|rule| (&rule.name, rule.ty, rule.expr.to_string()))
+                    .collect::<Vec<_>>()
+            })
+        } else {
+            self.raw_grammar.as_ref().map(|ast| {
+                ast.iter()
+                    .map(|rule| (&rule.name, rule.ty, rule.expr.to_string()))
+                    .collect()
+            })
+        }
+        .ok_or("DebuggerError::GrammarNotOpened".to_owned())?;
+        Ok(rules
+            .into_iter()
+            .map(|(name, ty, expr)| {
+                format!(
+                    "{} = {}{{ {} }}",
+                    name,
+                    RuleModifier::from(ty).symbol(),
+                    expr
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n"))
+    }
+
+    /// Parses the grammar, and returns its rules before and after the optimization.
+    /// (It's `pest_meta::parse_and_optimize`, except that it keeps the raw rules.)
+    fn parse_raw_grammar(
+        grammar: &str,
+    ) -> Result<GrammarAsts, Vec<pest::error::Error<parser::Rule>>> {
+        let pairs = parser::parse(parser::Rule::grammar_rules, grammar).map_err(|e| vec![e])?;
+        validator::validate_pairs(pairs.clone())?;
+        let ast = parser::consume_rules(pairs)?;
+        Ok((ast.clone(), optimizer::optimize(ast)))
+    }
+
+    fn parse_grammar(grammar: &str) -> Result<GrammarAsts, Vec<GrammarError>> {
+        match DebuggerContext::parse_raw_grammar(grammar) {
+            Ok(asts) => Ok(asts),
             Err(errors) => {
-                let msg = format!(
-                    "error parsing\n\n{}",
-                    errors
-                        .iter()
-                        .cloned()
-                        .map(|error| format!("{}", error.renamed_rules(rename_meta_rule)))
-                        .collect::<Vec<_>>()
-                        .join("\n")
-                );
-                Err(msg)
+                let mut errors = errors
+                    .into_iter()
+                    .map(|error| {
+                        let (line, col) = match error.line_col {
+                            LineColLocation::Pos(pos) => pos,
+                            LineColLocation::Span(start, _) => start,
+                        };
+                        GrammarError {
+                            line,
+                            col,
+                            message: error.renamed_rules(rename_meta_rule).to_string(),
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                // the undefined rules are summarized first, as typos are a common mistake
+                let undefined = analysis::undefined_rules(grammar);
+                if let Some(rule) = undefined.first() {
+                    let (_, line, col) = rule.usages[0];
+                    errors.insert(
+                        0,
+                        GrammarError {
+                            line,
+                            col,
+                            message: analysis::undefined_rules_message(&undefined),
+                        },
+                    );
+                }
+                Err(errors)
             }
         }
     }
 
-    /// Starts a debugger session: runs a rule on an input and stops at breakpoints.
-    /// When the debugger is stopped, an event is sent to the channel using `sender`.
-    /// The debugger can be resumed by calling `cont`.
+    /// Runs a rule on an input and returns all the debugger events
+    /// (i.e. the breakpoints encountered and the outcome of the parsing).
+    /// It returns an error if the grammar hasn't been loaded yet.
+    pub fn run_collect(&self, rule: &str, input: &str) -> Result<Vec<DebuggerEvent>, String> {
+        let ast = self
+            .grammar
+            .as_ref()
+            .ok_or("DebuggerError::GrammarNotOpened".to_owned())?;
+        Ok(self.collect(ast.clone(), rule, input, 0))
+    }
+
+    /// Like `run_collect`, but parses the loaded input.
+    /// It returns an error if the grammar or input haven't been loaded yet.
+    pub fn run_collect_loaded(&self, rule: &str) -> Result<Vec<DebuggerEvent>, String> {
+        self.run_collect_loaded_from(rule, 0)
+    }
+
+    /// Like `run_collect_loaded`, but only stops at the breakpoints
+    /// hit at or after the `from_pos` byte offset.
+    /// If the parsing ends before reaching it, only its outcome is returned.
+    pub fn run_collect_loaded_from(
+        &self,
+        rule: &str,
+        from_pos: usize,
+    ) -> Result<Vec<DebuggerEvent>, String> {
+        let ast = self
+            .grammar
+            .as_ref()
+            .ok_or("DebuggerError::GrammarNotOpened".to_owned())?;
+        let input = self
+            .input
+            .as_ref()
+            .ok_or("DebuggerError::InputNotOpened".to_owned())?;
+        Ok(self.collect(ast.clone(), rule, input, from_pos))
+    }
+
+    /// Runs a rule on each of the inputs and returns the error message
+    /// of each input whose parsing failed (i.e. it didn't reach `Eof`).
+    /// It returns an error if the grammar hasn't been loaded yet.
+    pub fn run_batch(&self, rule: &str, inputs: &[String]) -> Result<Vec<Option<String>>, String> {
+        let ast = self
+            .grammar
+            .as_ref()
+            .ok_or("DebuggerError::GrammarNotOpened".to_owned())?;
+        Ok(inputs
+            .iter()
+            .map(|input| {
+                self.collect(ast.clone(), rule, input, 0)
+                    .iter()
+                    .rev()
+                    .find_map(|event| match event {
+                        DebuggerEvent::Eof => Some(None),
+                        DebuggerEvent::Error(error, _) => Some(Some(error.clone())),
+                        DebuggerEvent::Aborted(_) => Some(Some(event.to_string())),
+                        _ => None,
+                    })
+                    .flatten()
+            })
+            .collect())
+    }
+
+    /// Calls `f` with the breakpoint events streamed to the channel using `rsender`.
+    #[cfg(feature = "web")]
+    fn streamed<T>(
+        &self,
+        rsender: &WorkerLink<Worker>,
+        handler_id: HandlerId,
+        f: impl FnOnce() -> T,
+    ) -> T {
+        let (link, epoch) = (rsender.clone(), self.epoch());
+        streaming(
+            move |events| {
+                for event in events {
+                    link.respond(handler_id, (epoch, event));
+                }
+            },
+            f,
+        )
+    }
+
+    /// Starts a debugger session: runs a rule on the loaded input
+    /// and sends the events (tagged with the current epoch) to the channel using `rsender`
+    /// (the breakpoints as they are hit, the rest once the parsing is done).
     /// This naturally returns errors if the grammar or input haven't been loaded yet etc.
+    #[cfg(feature = "web")]
     pub fn run(
         &mut self,
         rule: &str,
         rsender: WorkerLink<Worker>,
         handler_id: HandlerId,
     ) -> Result<(), String> {
-        let ast = self
-            .grammar
-            .as_ref()
-            .ok_or("DebuggerError::GrammarNotOpened".to_owned())?;
-        match self.input {
-            Some(ref input) => {
-                let rule = rule.to_owned();
-                let input = input.clone();
+        let events = self.streamed(&rsender, handler_id, || self.run_collect_loaded(rule))?;
+        for event in events {
+            rsender.respond(handler_id, (self.epoch(), event));
+        }
+        Ok(())
+    }
 
-                self.handle(ast.clone(), rule, input, rsender, handler_id);
-                Ok(())
-            }
-            None => Err("DebuggerError::InputNotOpened".to_owned()),
+    /// Like `run`, but only stops at the breakpoints hit
+    /// at or after the `from_pos` byte offset.
+    #[cfg(feature = "web")]
+    pub fn run_to_position(
+        &mut self,
+        rule: &str,
+        from_pos: usize,
+        rsender: WorkerLink<Worker>,
+        handler_id: HandlerId,
+    ) -> Result<(), String> {
+        let events = self.streamed(&rsender, handler_id, || {
+            self.run_collect_loaded_from(rule, from_pos)
+        })?;
+        for event in events {
+            rsender.respond(handler_id, (self.epoch(), event));
         }
+        Ok(())
+    }
+
+    /// Like `run`, but parses the provided input instead of the loaded one.
+    #[cfg(feature = "web")]
+    pub fn run_on(
+        &mut self,
+        rule: &str,
+        input: String,
+        rsender: WorkerLink<Worker>,
+        handler_id: HandlerId,
+    ) -> Result<(), String> {
+        let events = self.streamed(&rsender, handler_id, || self.run_collect(rule, &input))?;
+        for event in events {
+            rsender.respond(handler_id, (self.epoch(), event));
+        }
+        Ok(())
     }
 }
 
@@ -170,12 +975,24 @@ impl DebuggerContext {
 /// it doesn't seem necessary to run it in a worker.
 /// Anyway, it's kept in case there's a way to mimic that parsing pausing/resuming
 /// behaviour in WASM.
+///
+/// Only one debugger session runs at a time:
+/// a `Run` (or `RunOn`) is ignored until the previous session is ended
+/// with `EndSession` (or `Reset`), so that the events of the sessions don't interleave.
+///
+/// All the responses are tagged with the epoch of the debugger context,
+/// so that the events of outdated runs can be told apart.
+#[cfg(feature = "web")]
 pub struct Worker {
     link: WorkerLink<Self>,
     debugger_context: DebuggerContext,
+    /// whether the events of a debugger session were sent
+    /// and the session hasn't been ended yet
+    in_session: bool,
 }
 
 /// Possible messages that can be sent to the worker.
+#[cfg(feature = "web")]
 #[derive(Serialize, Deserialize)]
 pub enum WorkerInput {
     /// Loads a grammar from a string.
@@ -184,6 +1001,11 @@ pub enum WorkerInput {
     LoadInput(String),
     /// Adds a breakpoint at a provided rule name.
     AddBreakpoint(String),
+    /// Adds a breakpoint at a provided rule name
+    /// that is only hit within the provided (inclusive) position window.
+    AddConditionalBreakpoint(String, usize, usize),
+    /// Sets the number of hits to ignore for a provided rule name.
+    SetBreakpointIgnore(String, usize),
     /// Removes a breakpoint at a provided rule name.
     DeleteBreakpoint(String),
     /// Removes all breakpoints.
@@ -192,17 +1014,66 @@ pub enum WorkerInput {
     AddAllRulesBreakpoints,
     /// Starts a debugger session on a provided rule.
     Run(String),
+    /// Starts a debugger session on a provided rule and input
+    /// (instead of the loaded one).
+    RunOn(String, String),
+    /// Starts a debugger session on a provided rule
+    /// that only stops at the breakpoints hit at or after a provided position.
+    RunToPosition(String, usize),
+    /// Runs a provided rule on each of the provided inputs in turn
+    /// (without starting a debugger session) and responds with their `BatchResult`s.
+    RunBatch(String, Vec<String>),
+    /// Ends the current debugger session (once all its events have been stepped through
+    /// or it's stopped), so that the next session can be started.
+    EndSession,
+    /// Clears the grammar, input and breakpoints.
+    Reset,
+    /// Sets the maximum number of breakpoint events collected in a debugger session.
+    SetEventLimit(usize),
+    /// Sets the maximum number of rule attempts in a debugger session.
+    SetStepLimit(usize),
+    /// Sets whether the CRLF line endings of the loaded inputs are converted to LF.
+    SetNormalizeLineEndings(bool),
+    /// Requests the `Ast` dump of the loaded grammar: optimized (`true`) or raw (`false`).
+    RequestAst(bool),
+}
+
+#[cfg(feature = "web")]
+impl WorkerInput {
+    /// Whether the message starts a new epoch of the debugger context,
+    /// i.e. it outdates the events of the previous runs.
+    /// The sender can count these messages to know the epoch of the latest events.
+    pub fn bumps_epoch(&self) -> bool {
+        matches!(
+            self,
+            WorkerInput::LoadGrammar(_)
+                | WorkerInput::LoadInput(_)
+                | WorkerInput::EndSession
+                | WorkerInput::Reset
+        )
+    }
 }
 
+#[cfg(feature = "web")]
+impl Worker {
+    /// Sends an event tagged with the current epoch.
+    fn respond(&self, id: HandlerId, event: DebuggerEvent) {
+        self.link
+            .respond(id, (self.debugger_context.epoch(), event));
+    }
+}
+
+#[cfg(feature = "web")]
 impl yew_agent::Worker for Worker {
     type Input = WorkerInput;
     type Message = ();
-    type Output = DebuggerEvent;
+    type Output = (usize, DebuggerEvent);
     type Reach = Public<Self>;
     fn create(link: WorkerLink<Self>) -> Self {
         Self {
             link,
             debugger_context: Default::default(),
+            in_session: false,
         }
     }
 
@@ -214,39 +1085,109 @@ impl yew_agent::Worker for Worker {
         // this runs in a web worker
         // and does not block the main
         // browser thread!
+        if msg.bumps_epoch() {
+            self.debugger_context.bump_epoch();
+        }
         match msg {
             WorkerInput::LoadGrammar(ref grammar) => {
                 match self.debugger_context.load_grammar_direct(grammar) {
                     Ok(_) => {
-                        let rules = self
-                            .debugger_context
-                            .grammar
-                            .as_ref()
-                            .unwrap()
-                            .iter()
-                            .map(|x| x.name.clone())
-                            .collect();
-                        self.link.respond(id, DebuggerEvent::Rules(rules));
+                        self.respond(
+                            id,
+                            DebuggerEvent::Rules(self.debugger_context.rules.clone()),
+                        );
+                        self.respond(
+                            id,
+                            DebuggerEvent::RuleGraph(self.debugger_context.rule_graph.clone()),
+                        );
+                        self.respond(
+                            id,
+                            DebuggerEvent::RuleAnalysis(
+                                self.debugger_context.rule_analysis.clone(),
+                            ),
+                        );
+                        self.respond(
+                            id,
+                            DebuggerEvent::RuleDiagrams(
+                                self.debugger_context.rule_diagrams.clone(),
+                            ),
+                        );
+                        for warning in self.debugger_context.warnings.iter() {
+                            self.respond(id, DebuggerEvent::Warning(warning.clone()));
+                        }
+                        // the app clears the warnings on a new grammar
+                        if let Some(warning) = self.debugger_context.input_warning() {
+                            self.respond(id, DebuggerEvent::Warning(warning));
+                        }
                     }
-                    Err(error) => {
-                        self.link.respond(id, DebuggerEvent::Error(error));
+                    Err(errors) => {
+                        self.respond(id, DebuggerEvent::GrammarErrors(errors));
                     }
                 }
             }
             WorkerInput::LoadInput(input) => {
                 self.debugger_context.load_input_direct(input);
+                if let Some(warning) = self.debugger_context.input_warning() {
+                    self.respond(id, DebuggerEvent::Warning(warning));
+                }
             }
+            WorkerInput::Run(_) | WorkerInput::RunOn(..) | WorkerInput::RunToPosition(..)
+                if self.in_session => {}
             WorkerInput::Run(ref rule) => {
                 match self.debugger_context.run(rule, self.link.clone(), id) {
-                    Ok(_) => {}
+                    Ok(_) => self.in_session = true,
+                    Err(error) => {
+                        self.respond(id, DebuggerEvent::Error(error, None));
+                    }
+                }
+            }
+            WorkerInput::RunOn(ref rule, input) => {
+                match self
+                    .debugger_context
+                    .run_on(rule, input, self.link.clone(), id)
+                {
+                    Ok(_) => self.in_session = true,
+                    Err(error) => {
+                        self.respond(id, DebuggerEvent::Error(error, None));
+                    }
+                }
+            }
+            WorkerInput::RunToPosition(ref rule, pos) => {
+                match self
+                    .debugger_context
+                    .run_to_position(rule, pos, self.link.clone(), id)
+                {
+                    Ok(_) => self.in_session = true,
                     Err(error) => {
-                        self.link.respond(id, DebuggerEvent::Error(error));
+                        self.respond(id, DebuggerEvent::Error(error, None));
                     }
                 }
             }
+            WorkerInput::RunBatch(ref rule, ref inputs) => {
+                match self.debugger_context.run_batch(rule, inputs) {
+                    Ok(results) => {
+                        for (index, result) in results.into_iter().enumerate() {
+                            self.respond(id, DebuggerEvent::BatchResult(index, result));
+                        }
+                    }
+                    Err(error) => {
+                        self.respond(id, DebuggerEvent::Error(error, None));
+                    }
+                }
+            }
+            WorkerInput::EndSession => {
+                self.in_session = false;
+            }
             WorkerInput::AddBreakpoint(rule) => {
                 self.debugger_context.add_breakpoint(rule);
             }
+            WorkerInput::AddConditionalBreakpoint(rule, start, end) => {
+                self.debugger_context
+                    .add_conditional_breakpoint(rule, start, end);
+            }
+            WorkerInput::SetBreakpointIgnore(rule, ignore) => {
+                self.debugger_context.set_breakpoint_ignore(rule, ignore);
+            }
             WorkerInput::DeleteBreakpoint(rule) => {
                 self.debugger_context.delete_breakpoint(&rule);
             }
@@ -256,6 +1197,24 @@ impl yew_agent::Worker for Worker {
             WorkerInput::AddAllRulesBreakpoints => {
                 let _ = self.debugger_context.add_all_rules_breakpoints();
             }
+            WorkerInput::Reset => {
+                self.debugger_context.reset();
+                self.in_session = false;
+                self.respond(id, DebuggerEvent::Rules(vec![]));
+            }
+            WorkerInput::SetEventLimit(limit) => {
+                self.debugger_context.set_event_limit(limit);
+            }
+            WorkerInput::SetStepLimit(limit) => {
+                self.debugger_context.set_step_limit(limit);
+            }
+            WorkerInput::SetNormalizeLineEndings(normalize) => {
+                self.debugger_context.set_normalize_line_endings(normalize);
+            }
+            WorkerInput::RequestAst(optimized) => match self.debugger_context.ast_dump(optimized) {
+                Ok(ast) => self.respond(id, DebuggerEvent::Ast(ast)),
+                Err(error) => self.respond(id, DebuggerEvent::Error(error, None)),
+            },
         }
     }
 
@@ -267,3 +1226,503 @@ impl yew_agent::Worker for Worker {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc, sync::atomic::Ordering};
+
+    use super::{
+        streaming, DebuggerContext, DebuggerEvent, ParseTreeNode, RuleModifier, CRLF_WARNING,
+    };
+
+    /// the default grammar of the web app
+    const GRAMMAR: &str = r#"alpha = { 'a'..'z' | 'A'..'Z' }
+
+digit = { '0'..'9' }
+
+ident = { (alpha | digit)+ }
+
+ident_list = _{ !digit ~ ident ~ (" " ~ ident)+ }"#;
+
+    fn context(input: &str) -> DebuggerContext {
+        let mut context = DebuggerContext::default();
+        context.load_grammar_direct(GRAMMAR).unwrap();
+        context.load_input_direct(input.to_owned());
+        context.add_breakpoint("ident".to_owned());
+        context.add_breakpoint("ident_list".to_owned());
+        context
+    }
+
+    /// Runs `ident_list` and returns the events without the (varying) timing.
+    fn run(context: &DebuggerContext) -> Vec<DebuggerEvent> {
+        context
+            .run_collect_loaded("ident_list")
+            .unwrap()
+            .into_iter()
+            .filter(|event| !matches!(event, DebuggerEvent::Timing(_)))
+            .collect()
+    }
+
+    fn breakpoint(rule: &str, pos: usize, end: Option<usize>, stack: &[&str]) -> DebuggerEvent {
+        DebuggerEvent::Breakpoint(
+            rule.to_owned(),
+            pos,
+            end,
+            stack.iter().map(|rule| rule.to_string()).collect(),
+        )
+    }
+
+    fn exit(rule: &str, pos: usize, matched: bool) -> DebuggerEvent {
+        DebuggerEvent::RuleExit(rule.to_owned(), pos, matched)
+    }
+
+    #[test]
+    fn successful_run() {
+        let mut events = run(&context("hello world"));
+        assert_eq!(events.pop(), Some(DebuggerEvent::Eof));
+        let Some(DebuggerEvent::ParseTreeJson(tree)) = events.pop() else {
+            panic!("no parse tree");
+        };
+        let tree: Vec<ParseTreeNode> = serde_json::from_str(&tree).unwrap();
+        assert_eq!(
+            tree.iter()
+                .map(|node| (node.rule.as_str(), node.text.as_str()))
+                .collect::<Vec<_>>(),
+            [("ident", "hello"), ("ident", "world")]
+        );
+        assert_eq!(
+            events,
+            [
+                breakpoint("ident_list", 0, None, &["ident_list"]),
+                breakpoint("ident", 0, Some(5), &["ident_list", "ident"]),
+                exit("ident", 5, true),
+                breakpoint("ident", 6, Some(11), &["ident_list", "ident"]),
+                exit("ident", 11, true),
+                exit("ident_list", 11, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn streamed_run() {
+        let context = context("hello world");
+        let streamed = Rc::new(RefCell::new(vec![]));
+        let sink = streamed.clone();
+        let events = streaming(
+            move |events| sink.borrow_mut().extend(events),
+            || run(&context),
+        );
+        let mut streamed = streamed.take();
+        // the breakpoints are streamed as they are hit, before their end positions are known
+        assert_eq!(
+            streamed[..2],
+            [
+                breakpoint("ident_list", 0, None, &["ident_list"]),
+                breakpoint("ident", 0, None, &["ident_list", "ident"]),
+            ]
+        );
+        // once corrected, and followed by the other events, they are the events of the run
+        for event in events {
+            match event {
+                DebuggerEvent::Correction(index, pos) => match &mut streamed[index] {
+                    DebuggerEvent::Breakpoint(_, _, end, _) => *end = Some(pos),
+                    DebuggerEvent::RuleExit(_, exit, _) => *exit = pos,
+                    _ => panic!("correction of {}", streamed[index]),
+                },
+                event => streamed.push(event),
+            }
+        }
+        assert_eq!(streamed, run(&context));
+    }
+
+    #[test]
+    fn outdated_run() {
+        let context = context(&"hello ".repeat(1000));
+        let counter = context.epoch_counter();
+        let batches = Rc::new(RefCell::new(0));
+        let sink = batches.clone();
+        let events = streaming(
+            move |_| {
+                // e.g. the grammar is changed while parsing
+                *sink.borrow_mut() += 1;
+                counter.fetch_add(1, Ordering::SeqCst);
+            },
+            || run(&context),
+        );
+        // the parse is aborted right after the epoch moves on
+        assert_eq!(*batches.borrow(), 1);
+        assert_eq!(events, []);
+    }
+
+    #[test]
+    fn failed_run() {
+        let events = run(&context("1abc"));
+        assert!(matches!(
+            events.as_slice(),
+            [first, second, third, DebuggerEvent::Error(_, Some(0))]
+                if *first == breakpoint("ident_list", 0, None, &["ident_list"])
+                    && *second == exit("ident_list", 0, false)
+                    // `ident_list` starts with `!digit`
+                    && *third == DebuggerEvent::Expected(vec![], vec!["digit".to_owned()])
+        ));
+    }
+
+    #[test]
+    fn partial_parse() {
+        let outcome = |grammar: &str, rule: &str, input: &str| {
+            let mut context = DebuggerContext::default();
+            context.load_grammar_direct(grammar).unwrap();
+            context
+                .run_collect(rule, input)
+                .unwrap()
+                .into_iter()
+                .filter(|event| {
+                    matches!(event, DebuggerEvent::PartialParse(_) | DebuggerEvent::Eof)
+                })
+                .collect::<Vec<_>>()
+        };
+        // silent start rules (or ending in a literal) don't end with a pair
+        let grammar = "item = { \"a\" }\nfile = _{ item ~ \";\" }\nlits = _{ \"a\" ~ \";\" }";
+        assert_eq!(outcome(grammar, "file", "a;"), [DebuggerEvent::Eof]);
+        assert_eq!(outcome(grammar, "lits", "a;"), [DebuggerEvent::Eof]);
+        assert_eq!(
+            outcome(grammar, "file", "a;b"),
+            [DebuggerEvent::PartialParse(2), DebuggerEvent::Eof]
+        );
+        // as well as the built-in rules
+        assert_eq!(
+            outcome(grammar, "ASCII_DIGIT", "12"),
+            [DebuggerEvent::PartialParse(1), DebuggerEvent::Eof]
+        );
+    }
+
+    #[test]
+    fn failed_silent_start_rule() {
+        let mut context = DebuggerContext::default();
+        context.load_grammar_direct("file = _{ \"a\" }").unwrap();
+        let events = context.run_collect("file", "b").unwrap();
+        // the root rule wrapping the start rule is reported as the start rule
+        assert!(events.contains(&DebuggerEvent::Expected(vec!["file".to_owned()], vec![])));
+        assert!(events.iter().any(|event| matches!(
+            event,
+            DebuggerEvent::Error(error, Some(0)) if error.contains("expected \"file\"")
+        )));
+    }
+
+    #[test]
+    fn overridden_builtin() {
+        let mut context = DebuggerContext::default();
+        context
+            .load_grammar_direct("NEWLINE = { \";\" }\nline = { \"a\" ~ NEWLINE }")
+            .unwrap();
+        assert_eq!(context.warnings.len(), 1);
+        assert!(context.warnings[0].ends_with(": NEWLINE"));
+        context
+            .load_grammar_direct("newline = { \";\" }\nline = { \"a\" ~ newline }")
+            .unwrap();
+        assert!(context.warnings.is_empty());
+    }
+
+    #[test]
+    fn unreferenced_rules() {
+        let mut context = DebuggerContext::default();
+        // a single unreferenced rule is the entry rule
+        context.load_grammar_direct(GRAMMAR).unwrap();
+        assert!(context.warnings.is_empty());
+        context
+            .load_grammar_direct("file = { item+ }\nitem = { \"a\" }\nold = { item ~ \";\" }")
+            .unwrap();
+        assert_eq!(context.warnings.len(), 1);
+        assert!(context.warnings[0].ends_with(": file, old"));
+    }
+
+    #[test]
+    fn empty_grammar() {
+        let mut context = DebuggerContext::default();
+        context.load_grammar_direct("a = { \"a\" }").unwrap();
+        assert!(context.warnings.is_empty());
+        context.load_grammar_direct("").unwrap();
+        assert!(context.rules.is_empty());
+        assert!(context.rule_graph.is_empty());
+        assert_eq!(context.warnings, ["grammar is empty"]);
+        context
+            .load_grammar_direct("  // only a comment\n")
+            .unwrap();
+        assert_eq!(context.warnings, ["grammar is empty"]);
+    }
+
+    #[test]
+    fn nullable_rules() {
+        let mut context = DebuggerContext::default();
+        context
+            .load_grammar_direct("a = { \"a\"* }\nb = { a ~ \"b\" }\nc = { a ~ b? }")
+            .unwrap();
+        assert_eq!(context.rule_analysis.nullable, ["a", "c"]);
+    }
+
+    #[test]
+    fn first_sets() {
+        let mut context = DebuggerContext::default();
+        context
+            .load_grammar_direct(
+                "a = { \"x\"? ~ ^\"b\" }\nb = { a | '0'..'9' | ASCII_DIGIT }\nc = { !\"c\" ~ b* ~ EOI }\nd = { \"(\" ~ d ~ \")\" | \"e\" }",
+            )
+            .unwrap();
+        let first = |rule: &str| {
+            context
+                .rule_analysis
+                .first
+                .iter()
+                .find(|(name, _)| name == rule)
+                .map(|(_, first)| first.join(", "))
+        };
+        assert_eq!(first("a").as_deref(), Some("'B', 'b', 'x'"));
+        assert_eq!(
+            first("b").as_deref(),
+            Some("'0'..'9', 'B', 'b', 'x', ASCII_DIGIT")
+        );
+        // `b*` is nullable, so `c` can begin with `EOI` too
+        assert_eq!(
+            first("c").as_deref(),
+            Some("'0'..'9', 'B', 'b', 'x', ASCII_DIGIT, EOI")
+        );
+        // the recursion doesn't prevent the fixpoint
+        assert_eq!(first("d").as_deref(), Some("'(', 'e'"));
+    }
+
+    #[test]
+    fn grammar_errors() {
+        let mut context = DebuggerContext::default();
+        let errors = context
+            .load_grammar_direct("a = { \"a\" }\nb = { \"\"* }\nc = { PUSH(\"\")* }")
+            .unwrap_err();
+        // each error is reported separately, with its location
+        assert_eq!(
+            errors
+                .iter()
+                .map(|error| (error.line, error.col))
+                .collect::<Vec<_>>(),
+            [(2, 7), (3, 7)]
+        );
+        assert!(errors
+            .iter()
+            .all(|error| error.message.ends_with("will repeat infinitely")));
+    }
+
+    #[test]
+    fn left_recursive_grammar() {
+        let mut context = DebuggerContext::default();
+        // pest rejects left recursion (even through nullable prefixes) as a grammar error
+        for grammar in [
+            "a = { a ~ \"x\" | \"x\" }",
+            "a = { b ~ \"x\" }\nb = { a | \"y\" }",
+            "e = { \"\"? }\na = { e ~ a ~ \"x\" }",
+        ] {
+            let errors = context.load_grammar_direct(grammar).unwrap_err();
+            assert!(
+                errors
+                    .iter()
+                    .any(|error| error.message.contains("rule a is left-recursive")),
+                "{}",
+                grammar
+            );
+        }
+        // but not the left recursion after a stack operation, which is only a warning
+        for (grammar, rules) in [
+            ("a = { PEEK ~ a ~ \"x\" | \"x\" }", "a"),
+            ("a = { b ~ \"x\" }\nb = { PEEK_ALL ~ a | \"y\" }", "a, b"),
+        ] {
+            context.load_grammar_direct(grammar).unwrap();
+            assert!(
+                context
+                    .warnings
+                    .iter()
+                    .any(|warning| warning.starts_with("left-recursive rules")
+                        && warning.ends_with(&format!(": {}", rules))),
+                "{}: {:?}",
+                grammar,
+                context.warnings
+            );
+        }
+        context.load_grammar_direct(GRAMMAR).unwrap();
+        assert!(!context
+            .warnings
+            .iter()
+            .any(|warning| warning.starts_with("left-recursive rules")));
+    }
+
+    #[test]
+    fn run_to_position() {
+        let mut context = DebuggerContext::default();
+        context.load_grammar_direct(GRAMMAR).unwrap();
+        context.load_input_direct("hello world".to_owned());
+        context.add_breakpoint("ident".to_owned());
+        let events = context.run_collect_loaded_from("ident_list", 3).unwrap();
+        // the first breakpoint of `ident` (at 0) is skipped
+        assert_eq!(
+            events
+                .iter()
+                .find(|event| matches!(event, DebuggerEvent::Breakpoint(..))),
+            Some(&breakpoint("ident", 6, Some(11), &["ident_list", "ident"]))
+        );
+        // the parsing ends before the position is reached
+        let events = context.run_collect_loaded_from("ident_list", 100).unwrap();
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, DebuggerEvent::Breakpoint(..))));
+        assert_eq!(events.last(), Some(&DebuggerEvent::Eof));
+    }
+
+    #[test]
+    fn typed_start_rule() {
+        let context = context("hello");
+        // a built-in rule can be run too
+        assert_eq!(
+            context.run_collect_loaded("ASCII_ALPHA").unwrap().last(),
+            Some(&DebuggerEvent::Eof)
+        );
+        assert_eq!(
+            context.run_collect_loaded("idnet").unwrap().last(),
+            Some(&DebuggerEvent::Error(
+                "the rule idnet isn't defined in the grammar (nor built into pest)".to_owned(),
+                None
+            ))
+        );
+    }
+
+    #[test]
+    fn empty_input() {
+        let mut context = DebuggerContext::default();
+        context
+            .load_grammar_direct("eoi = { EOI }\nident = { ASCII_ALPHA+ ~ EOI }")
+            .unwrap();
+        context.load_input_direct(String::new());
+        context.add_breakpoint("eoi".to_owned());
+        context.add_breakpoint("ident".to_owned());
+        let events = context
+            .run_collect_loaded("eoi")
+            .unwrap()
+            .into_iter()
+            .filter(|event| !matches!(event, DebuggerEvent::Timing(_)))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            events.first(),
+            Some(&breakpoint("eoi", 0, Some(0), &["eoi"]))
+        );
+        assert_eq!(events.last(), Some(&DebuggerEvent::Eof));
+        let events = context.run_collect_loaded("ident").unwrap();
+        assert!(matches!(
+            events.last(),
+            Some(DebuggerEvent::Error(_, Some(0)))
+        ));
+    }
+
+    #[test]
+    fn crlf_line_endings() {
+        let mut context = DebuggerContext::default();
+        context
+            .load_grammar_direct("line = { \"a\" ~ \"\\n\" }")
+            .unwrap();
+        context.load_input_direct("a\r\n".to_owned());
+        assert_eq!(context.input_warning().as_deref(), Some(CRLF_WARNING));
+        assert!(matches!(
+            context.run_collect_loaded("line").unwrap().last(),
+            Some(DebuggerEvent::Error(..))
+        ));
+        context.set_normalize_line_endings(true);
+        context.load_input_direct("a\r\n".to_owned());
+        assert_eq!(context.input_warning(), None);
+        assert_eq!(
+            context.run_collect_loaded("line").unwrap().last(),
+            Some(&DebuggerEvent::Eof)
+        );
+    }
+
+    #[test]
+    fn raw_and_optimized_ast() {
+        let mut context = DebuggerContext::default();
+        assert!(context.ast_dump(true).is_err());
+        context
+            .load_grammar_direct("a = @{ \"a\" ~ \"b\" }\nb = _{ a* }")
+            .unwrap();
+        assert_eq!(
+            context.ast_dump(false).unwrap(),
+            "a = @{ (\"a\" ~ \"b\") }\nb = _{ a* }"
+        );
+        // the optimizer concatenates the strings of atomic rules
+        assert_eq!(
+            context.ast_dump(true).unwrap(),
+            "a = @{ \"ab\" }\nb = _{ a* }"
+        );
+    }
+
+    #[test]
+    fn rule_modifiers() {
+        let mut context = DebuggerContext::default();
+        context
+            .load_grammar_direct("a = @{ \"a\" }\nb = _{ a }\nc = { b }")
+            .unwrap();
+        assert_eq!(
+            context.rules,
+            [
+                ("a".to_owned(), RuleModifier::Atomic),
+                ("b".to_owned(), RuleModifier::Silent),
+                ("c".to_owned(), RuleModifier::Normal),
+            ]
+        );
+    }
+
+    #[test]
+    fn batch_run() {
+        let inputs = ["hello world", "1abc", "hello"].map(str::to_owned);
+        let results = context("").run_batch("ident_list", &inputs).unwrap();
+        assert!(matches!(results.as_slice(), [None, Some(_), Some(_)]));
+    }
+
+    #[test]
+    fn internal_parser_panic() {
+        let mut context = DebuggerContext::default();
+        context.load_grammar_direct("pop = { POP }").unwrap();
+        context.add_breakpoint("pop".to_owned());
+        let events = context.run_collect("pop", "a").unwrap();
+        assert_eq!(
+            events.last(),
+            Some(&DebuggerEvent::Error(
+                "internal parser panic: pop was called on empty stack".to_owned(),
+                None
+            ))
+        );
+    }
+
+    #[test]
+    fn step_limit() {
+        let mut context = DebuggerContext::default();
+        context.load_grammar_direct(GRAMMAR).unwrap();
+        context.set_step_limit(10);
+        let events = context.run_collect("ident_list", "hello world").unwrap();
+        assert_eq!(events.last(), Some(&DebuggerEvent::Aborted(10)));
+    }
+
+    #[test]
+    fn grammar_not_opened() {
+        let context = DebuggerContext::default();
+        assert_eq!(
+            context.run_collect("ident", "hello"),
+            Err("DebuggerError::GrammarNotOpened".to_owned())
+        );
+        assert_eq!(
+            context.run_collect_loaded("ident"),
+            Err("DebuggerError::GrammarNotOpened".to_owned())
+        );
+    }
+
+    #[test]
+    fn input_not_opened() {
+        let mut context = DebuggerContext::default();
+        context.load_grammar_direct(GRAMMAR).unwrap();
+        assert_eq!(
+            context.run_collect_loaded("ident"),
+            Err("DebuggerError::InputNotOpened".to_owned())
+        );
+    }
+}
@@ -0,0 +1,392 @@
+use std::collections::HashSet;
+
+use pest_meta::optimizer::{OptimizedExpr, OptimizedRule};
+use serde::{Deserialize, Serialize};
+
+/// The structure of a rule's expression, as drawn in a railroad (syntax) diagram.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RuleDiagram {
+    /// a string, range or built-in rule to match
+    Terminal(String),
+    /// a call of a grammar rule
+    NonTerminal(String),
+    Sequence(Vec<RuleDiagram>),
+    Choice(Vec<RuleDiagram>),
+    Optional(Box<RuleDiagram>),
+    ZeroOrMore(Box<RuleDiagram>),
+    OneOrMore(Box<RuleDiagram>),
+    /// an expression with an annotation, e.g. a predicate (`&` or `!`) or a node tag
+    Labeled(String, Box<RuleDiagram>),
+}
+
+impl RuleDiagram {
+    /// Builds the diagram of an expression;
+    /// `rules` are the names of the grammar rules (the other identifiers are built-in rules).
+    pub fn new(expr: &OptimizedExpr, rules: &HashSet<&str>) -> Self {
+        let labeled = |label: &str, expr: &OptimizedExpr| {
+            RuleDiagram::Labeled(label.to_owned(), Box::new(RuleDiagram::new(expr, rules)))
+        };
+        match expr {
+            OptimizedExpr::Str(string) => RuleDiagram::Terminal(format!("{:?}", string)),
+            OptimizedExpr::Insens(string) => RuleDiagram::Terminal(format!("^{:?}", string)),
+            OptimizedExpr::Range(start, end) => {
+                RuleDiagram::Terminal(format!("'{}'..'{}'", start, end))
+            }
+            OptimizedExpr::Ident(ident) if rules.contains(ident.as_str()) => {
+                RuleDiagram::NonTerminal(ident.clone())
+            }
+            OptimizedExpr::Ident(ident) => RuleDiagram::Terminal(ident.clone()),
+            OptimizedExpr::PeekSlice(start, end) => RuleDiagram::Terminal(format!(
+                "PEEK[{}..{}]",
+                start,
+                end.map(|end| end.to_string()).unwrap_or_default()
+            )),
+            OptimizedExpr::Skip(strings) => {
+                RuleDiagram::Terminal(format!("(skip to {:?})", strings.join(" | ")))
+            }
+            OptimizedExpr::PushLiteral(string) => {
+                RuleDiagram::Terminal(format!("PUSH_LITERAL({:?})", string))
+            }
+            OptimizedExpr::Seq(..) => {
+                let mut items = vec![];
+                flatten(expr, &mut items, &|expr| match expr {
+                    OptimizedExpr::Seq(lhs, rhs) => Some((lhs, rhs)),
+                    _ => None,
+                });
+                RuleDiagram::Sequence(items.iter().map(|e| RuleDiagram::new(e, rules)).collect())
+            }
+            OptimizedExpr::Choice(..) => {
+                let mut items = vec![];
+                flatten(expr, &mut items, &|expr| match expr {
+                    OptimizedExpr::Choice(lhs, rhs) => Some((lhs, rhs)),
+                    _ => None,
+                });
+                RuleDiagram::Choice(items.iter().map(|e| RuleDiagram::new(e, rules)).collect())
+            }
+            OptimizedExpr::Opt(expr) => {
+                RuleDiagram::Optional(Box::new(RuleDiagram::new(expr, rules)))
+            }
+            OptimizedExpr::Rep(expr) => {
+                RuleDiagram::ZeroOrMore(Box::new(RuleDiagram::new(expr, rules)))
+            }
+            OptimizedExpr::RepOnce(expr) => {
+                RuleDiagram::OneOrMore(Box::new(RuleDiagram::new(expr, rules)))
+            }
+            OptimizedExpr::PosPred(expr) => labeled("&", expr),
+            OptimizedExpr::NegPred(expr) => labeled("!", expr),
+            OptimizedExpr::Push(expr) => labeled("PUSH", expr),
+            OptimizedExpr::NodeTag(expr, tag) => labeled(&format!("#{}", tag), expr),
+            OptimizedExpr::RestoreOnErr(expr) => RuleDiagram::new(expr, rules),
+        }
+    }
+}
+
+/// Collects the operands of nested binary expressions (e.g. `a ~ (b ~ c)`) in order.
+fn flatten<'a>(
+    expr: &'a OptimizedExpr,
+    items: &mut Vec<&'a OptimizedExpr>,
+    split: &impl Fn(&'a OptimizedExpr) -> Option<(&'a OptimizedExpr, &'a OptimizedExpr)>,
+) {
+    match split(expr) {
+        Some((lhs, rhs)) => {
+            flatten(lhs, items, split);
+            flatten(rhs, items, split);
+        }
+        None => items.push(expr),
+    }
+}
+
+/// Returns the diagram of each rule of the grammar, in the grammar order.
+pub fn rule_diagrams(ast: &[OptimizedRule]) -> Vec<(String, RuleDiagram)> {
+    let rules = ast.iter().map(|rule| rule.name.as_str()).collect();
+    ast.iter()
+        .map(|rule| (rule.name.clone(), RuleDiagram::new(&rule.expr, &rules)))
+        .collect()
+}
+
+/// The approximate width of a character of the (monospace) diagram font.
+#[cfg(feature = "web")]
+const CHAR_WIDTH: i32 = 8;
+/// The height of the terminal and non-terminal boxes.
+#[cfg(feature = "web")]
+pub const BOX_HEIGHT: i32 = 24;
+/// The horizontal padding of the box texts.
+#[cfg(feature = "web")]
+const PADDING: i32 = 8;
+/// The length of the lines between the items of a sequence.
+#[cfg(feature = "web")]
+const GAP: i32 = 16;
+/// The room on each side of a choice or loop for its branching lines.
+#[cfg(feature = "web")]
+const ARC: i32 = 16;
+/// The vertical space between the branches of a choice or loop.
+#[cfg(feature = "web")]
+const VGAP: i32 = 8;
+/// The height of the labels above labeled expressions.
+#[cfg(feature = "web")]
+const LABEL_HEIGHT: i32 = 14;
+
+/// A shape of a drawn diagram, in SVG user units.
+#[cfg(feature = "web")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Shape {
+    /// a box with a text; terminals have rounded boxes
+    Box {
+        x: i32,
+        y: i32,
+        width: i32,
+        text: String,
+        rounded: bool,
+    },
+    /// a line through the points
+    Line(Vec<(i32, i32)>),
+    /// a label whose baseline starts at the point
+    Label(i32, i32, String),
+}
+
+/// The size of a drawn expression; it's entered and exited at `baseline` (from its top).
+#[cfg(feature = "web")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Layout {
+    width: i32,
+    height: i32,
+    baseline: i32,
+}
+
+#[cfg(feature = "web")]
+fn text_width(text: &str) -> i32 {
+    text.chars().count() as i32 * CHAR_WIDTH
+}
+
+#[cfg(feature = "web")]
+fn optional_of(diagram: &RuleDiagram) -> RuleDiagram {
+    RuleDiagram::Choice(vec![RuleDiagram::Sequence(vec![]), diagram.clone()])
+}
+
+#[cfg(feature = "web")]
+fn layout(diagram: &RuleDiagram) -> Layout {
+    match diagram {
+        RuleDiagram::Terminal(text) | RuleDiagram::NonTerminal(text) => Layout {
+            width: text_width(text) + 2 * PADDING,
+            height: BOX_HEIGHT,
+            baseline: BOX_HEIGHT / 2,
+        },
+        RuleDiagram::Sequence(items) if items.is_empty() => Layout {
+            width: GAP,
+            height: 0,
+            baseline: 0,
+        },
+        RuleDiagram::Sequence(items) => {
+            let layouts = items.iter().map(layout).collect::<Vec<_>>();
+            let baseline = layouts.iter().map(|l| l.baseline).max().unwrap_or(0);
+            let below = layouts
+                .iter()
+                .map(|l| l.height - l.baseline)
+                .max()
+                .unwrap_or(0);
+            Layout {
+                width: layouts.iter().map(|l| l.width).sum::<i32>()
+                    + GAP * (layouts.len() as i32 - 1),
+                height: baseline + below,
+                baseline,
+            }
+        }
+        RuleDiagram::Choice(items) => {
+            let layouts = items.iter().map(layout).collect::<Vec<_>>();
+            Layout {
+                width: layouts.iter().map(|l| l.width).max().unwrap_or(0) + 2 * ARC,
+                height: layouts.iter().map(|l| l.height).sum::<i32>()
+                    + VGAP * (layouts.len() as i32 - 1).max(0),
+                baseline: layouts.first().map(|l| l.baseline).unwrap_or(0),
+            }
+        }
+        RuleDiagram::Optional(item) => layout(&optional_of(item)),
+        RuleDiagram::ZeroOrMore(item) => {
+            layout(&optional_of(&RuleDiagram::OneOrMore(item.clone())))
+        }
+        RuleDiagram::OneOrMore(item) => {
+            let item = layout(item);
+            Layout {
+                width: item.width + 2 * ARC,
+                height: item.height + VGAP,
+                baseline: item.baseline,
+            }
+        }
+        RuleDiagram::Labeled(label, item) => {
+            let item = layout(item);
+            Layout {
+                width: item.width.max(text_width(label)),
+                height: item.height + LABEL_HEIGHT,
+                baseline: item.baseline + LABEL_HEIGHT,
+            }
+        }
+    }
+}
+
+/// Draws the diagram with its top left corner at (`x`, `y`).
+#[cfg(feature = "web")]
+fn draw(diagram: &RuleDiagram, x: i32, y: i32, shapes: &mut Vec<Shape>) {
+    let Layout {
+        width, baseline, ..
+    } = layout(diagram);
+    let line_y = y + baseline;
+    match diagram {
+        RuleDiagram::Terminal(text) | RuleDiagram::NonTerminal(text) => shapes.push(Shape::Box {
+            x,
+            y,
+            width,
+            text: text.clone(),
+            rounded: matches!(diagram, RuleDiagram::Terminal(_)),
+        }),
+        RuleDiagram::Sequence(items) if items.is_empty() => {
+            shapes.push(Shape::Line(vec![(x, line_y), (x + width, line_y)]));
+        }
+        RuleDiagram::Sequence(items) => {
+            let mut item_x = x;
+            for (i, item) in items.iter().enumerate() {
+                let item_layout = layout(item);
+                draw(item, item_x, line_y - item_layout.baseline, shapes);
+                item_x += item_layout.width;
+                if i + 1 < items.len() {
+                    shapes.push(Shape::Line(vec![(item_x, line_y), (item_x + GAP, line_y)]));
+                    item_x += GAP;
+                }
+            }
+        }
+        RuleDiagram::Choice(items) => {
+            let mut item_y = y;
+            for item in items {
+                let item_layout = layout(item);
+                let item_line_y = item_y + item_layout.baseline;
+                draw(item, x + ARC, item_y, shapes);
+                shapes.push(Shape::Line(vec![
+                    (x, line_y),
+                    (x + ARC / 2, line_y),
+                    (x + ARC / 2, item_line_y),
+                    (x + ARC, item_line_y),
+                ]));
+                shapes.push(Shape::Line(vec![
+                    (x + ARC + item_layout.width, item_line_y),
+                    (x + width - ARC / 2, item_line_y),
+                    (x + width - ARC / 2, line_y),
+                    (x + width, line_y),
+                ]));
+                item_y += item_layout.height + VGAP;
+            }
+        }
+        RuleDiagram::Optional(item) => draw(&optional_of(item), x, y, shapes),
+        RuleDiagram::ZeroOrMore(item) => draw(
+            &optional_of(&RuleDiagram::OneOrMore(item.clone())),
+            x,
+            y,
+            shapes,
+        ),
+        RuleDiagram::OneOrMore(item) => {
+            let item_layout = layout(item);
+            draw(item, x + ARC, y, shapes);
+            let loop_y = y + item_layout.height + VGAP;
+            shapes.push(Shape::Line(vec![(x, line_y), (x + ARC, line_y)]));
+            shapes.push(Shape::Line(vec![
+                (x + ARC + item_layout.width, line_y),
+                (x + width, line_y),
+            ]));
+            shapes.push(Shape::Line(vec![
+                (x + width - ARC / 2, line_y),
+                (x + width - ARC / 2, loop_y),
+                (x + ARC / 2, loop_y),
+                (x + ARC / 2, line_y),
+            ]));
+        }
+        RuleDiagram::Labeled(label, item) => {
+            let item_layout = layout(item);
+            shapes.push(Shape::Label(x, y + LABEL_HEIGHT - 4, label.clone()));
+            draw(item, x, y + LABEL_HEIGHT, shapes);
+            if item_layout.width < width {
+                shapes.push(Shape::Line(vec![
+                    (x + item_layout.width, line_y),
+                    (x + width, line_y),
+                ]));
+            }
+        }
+    }
+}
+
+/// Lays out the diagram with an entry and an exit line,
+/// and returns its width, height and shapes.
+#[cfg(feature = "web")]
+pub fn shapes(diagram: &RuleDiagram) -> (i32, i32, Vec<Shape>) {
+    let Layout {
+        width,
+        height,
+        baseline,
+    } = layout(diagram);
+    let mut shapes = vec![
+        Shape::Line(vec![(0, GAP + baseline), (GAP, GAP + baseline)]),
+        Shape::Line(vec![
+            (GAP + width, GAP + baseline),
+            (2 * GAP + width, GAP + baseline),
+        ]),
+    ];
+    draw(diagram, GAP, GAP, &mut shapes);
+    (width + 2 * GAP, height + 2 * GAP, shapes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rule_diagrams, RuleDiagram};
+
+    #[test]
+    fn diagram_of_rule() {
+        let (_, ast) = pest_meta::parse_and_optimize(
+            r#"
+            ident = { ("_" | ASCII_ALPHA) ~ (ASCII_ALPHANUMERIC | "_")* }
+            list = { ident ~ ("," ~ ident)* ~ ","? }
+            "#,
+        )
+        .unwrap();
+        let diagrams = rule_diagrams(&ast);
+        assert_eq!(diagrams[0].0, "ident");
+        assert_eq!(
+            diagrams[1].1,
+            RuleDiagram::Sequence(vec![
+                RuleDiagram::NonTerminal("ident".to_owned()),
+                RuleDiagram::ZeroOrMore(Box::new(RuleDiagram::Sequence(vec![
+                    RuleDiagram::Terminal("\",\"".to_owned()),
+                    RuleDiagram::NonTerminal("ident".to_owned()),
+                ]))),
+                RuleDiagram::Optional(Box::new(RuleDiagram::Terminal("\",\"".to_owned()))),
+            ])
+        );
+    }
+
+    #[cfg(feature = "web")]
+    #[test]
+    fn shapes_of_diagram() {
+        use super::{shapes, Shape};
+
+        let diagram = RuleDiagram::Choice(vec![
+            RuleDiagram::Terminal("\"a\"".to_owned()),
+            RuleDiagram::NonTerminal("b".to_owned()),
+        ]);
+        let (width, height, shapes) = shapes(&diagram);
+        // the boxes (3 characters wide) are stacked between the branching lines
+        assert_eq!(
+            (width, height),
+            (16 + 16 + 40 + 16 + 16, 16 + 24 + 8 + 24 + 16)
+        );
+        assert!(shapes.contains(&Shape::Box {
+            x: 32,
+            y: 16,
+            width: 40,
+            text: "\"a\"".to_owned(),
+            rounded: true,
+        }));
+        assert!(shapes.contains(&Shape::Box {
+            x: 32,
+            y: 48,
+            width: 24,
+            text: "b".to_owned(),
+            rounded: false,
+        }));
+    }
+}
@@ -0,0 +1,83 @@
+use wasm_bindgen::prelude::*;
+use web_sys::Element;
+use yew::AppHandle;
+
+use crate::app::{App, AppProps, Message};
+
+#[wasm_bindgen(inline_js = r#"
+export function define(name, mount) {
+    if (customElements.get(name)) {
+        return;
+    }
+    customElements.define(name, class extends HTMLElement {
+        connectedCallback() {
+            this.debugger = mount(
+                this,
+                this.getAttribute("grammar"),
+                this.getAttribute("input"),
+                this.getAttribute("rule"),
+            );
+        }
+
+        disconnectedCallback() {
+            this.debugger?.destroy();
+            this.debugger = undefined;
+        }
+
+        run() {
+            this.debugger?.run();
+        }
+    });
+}
+"#)]
+extern "C" {
+    fn define(
+        name: &str,
+        mount: &Closure<
+            dyn Fn(Element, Option<String>, Option<String>, Option<String>) -> DebuggerElement,
+        >,
+    );
+}
+
+/// The debugger mounted in a custom element.
+#[wasm_bindgen]
+pub struct DebuggerElement {
+    handle: AppHandle<App>,
+}
+
+#[wasm_bindgen]
+impl DebuggerElement {
+    /// Starts a debugger session (like the "Run" button).
+    pub fn run(&self) {
+        self.handle.send_message(Message::Run);
+    }
+
+    /// Unmounts the debugger.
+    pub fn destroy(self) {
+        self.handle.destroy();
+    }
+}
+
+/// Registers the custom element `name` (e.g. `pest-debugger`)
+/// that mounts the debugger with its `grammar`, `input` and `rule` (to run) attributes,
+/// and has a `run()` method.
+/// It's a no-op if the element is already defined.
+#[wasm_bindgen(js_name = defineElement)]
+pub fn define_element(name: &str) {
+    let mount = Closure::<
+        dyn Fn(Element, Option<String>, Option<String>, Option<String>) -> DebuggerElement,
+    >::new(|root, grammar, input, to_run| {
+        let props = AppProps {
+            grammar,
+            input,
+            to_run,
+            ..AppProps::default()
+        };
+        DebuggerElement {
+            handle: yew::Renderer::<App>::with_root_and_props(root, props).render(),
+        }
+    });
+    define(name, &mount);
+    // the elements can be connected at any time
+    mount.forget();
+}
@@ -0,0 +1,132 @@
+use pest::iterators::Pair;
+use pest_meta::parser::{self, Rule};
+
+/// Re-emits the grammar with normalized whitespace:
+/// one rule per line as `name = modifier{ expression }`,
+/// single spaces around the infix operators and one blank line between rules.
+/// Comments between rules are kept, but rules with comments inside
+/// are only re-indented.
+/// It returns `None` if the grammar can't be parsed.
+pub fn format_grammar(grammar: &str) -> Option<String> {
+    let pairs = parser::parse(Rule::grammar_rules, grammar).ok()?;
+    // each block is a rule with the comments and docs before it
+    let mut blocks = vec![];
+    let mut block = String::new();
+    let mut last_end = 0;
+    for pair in pairs {
+        let span = pair.as_span();
+        push_comments(&mut block, &grammar[last_end..span.start()]);
+        last_end = span.end();
+        match pair.as_rule() {
+            Rule::grammar_doc => {
+                block.push_str(pair.as_str().trim());
+                block.push('\n');
+            }
+            Rule::grammar_rule => {
+                let mut inner = pair.into_inner();
+                let first = inner.next()?;
+                if first.as_rule() == Rule::line_doc {
+                    block.push_str(first.as_str().trim());
+                    block.push('\n');
+                    continue;
+                }
+                block.push_str(first.as_str());
+                block.push_str(" = ");
+                for part in inner {
+                    match part.as_rule() {
+                        Rule::assignment_operator | Rule::opening_brace | Rule::closing_brace => {}
+                        Rule::expression => block.push_str(&format_body(part)),
+                        _ => block.push_str(part.as_str()),
+                    }
+                }
+                block.push('\n');
+                blocks.push(std::mem::take(&mut block));
+            }
+            _ => {}
+        }
+    }
+    push_comments(&mut block, &grammar[last_end..]);
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+    Some(blocks.join("\n"))
+}
+
+/// Appends the (trimmed) comment lines of the text between rules.
+fn push_comments(block: &mut String, text: &str) {
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        block.push_str(line);
+        block.push('\n');
+    }
+}
+
+/// Formats a rule's braced expression,
+/// or only re-indents it if it may contain comments (which the parser skips).
+fn format_body(expression: Pair<'_, Rule>) -> String {
+    let text = expression.as_str();
+    if text.contains("//") || text.contains("/*") {
+        let lines = text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .collect::<Vec<_>>();
+        format!("{{\n    {}\n}}", lines.join("\n    "))
+    } else {
+        format!("{{ {} }}", format_expression(expression))
+    }
+}
+
+fn format_expression(expression: Pair<'_, Rule>) -> String {
+    let mut formatted = String::new();
+    for part in expression.into_inner() {
+        match part.as_rule() {
+            Rule::term => formatted.push_str(&format_term(part)),
+            Rule::sequence_operator => formatted.push_str(" ~ "),
+            // a leading choice operator
+            Rule::choice_operator if formatted.is_empty() => formatted.push_str("| "),
+            Rule::choice_operator => formatted.push_str(" | "),
+            _ => formatted.push_str(part.as_str()),
+        }
+    }
+    formatted
+}
+
+fn format_term(term: Pair<'_, Rule>) -> String {
+    let mut formatted = String::new();
+    for part in term.into_inner() {
+        match part.as_rule() {
+            // a node tag
+            Rule::assignment_operator => formatted.push_str(" = "),
+            Rule::expression => formatted.push_str(&format_expression(part)),
+            Rule::repeat_exact | Rule::repeat_min | Rule::repeat_max | Rule::repeat_min_max => {
+                formatted.push_str(&part.as_str().split_whitespace().collect::<String>())
+            }
+            _ => formatted.push_str(part.as_str()),
+        }
+    }
+    formatted
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_grammar;
+
+    #[test]
+    fn normalizes_whitespace() {
+        let grammar = "alpha={'a'..'z'|'A'..'Z'}\n\n\n  ident =@ {(alpha|\"_\" )+ ~ \" \"{1 , 2}}";
+        assert_eq!(
+            format_grammar(grammar).unwrap(),
+            "alpha = { 'a'..'z' | 'A'..'Z' }\n\nident = @{ (alpha | \"_\")+ ~ \" \"{1,2} }\n"
+        );
+    }
+
+    #[test]
+    fn keeps_comments_and_docs() {
+        let grammar =
+            "//! grammar doc\n// comment\n/// rule doc\na = { \"a\" // trailing\n }\nb = { a }";
+        assert_eq!(
+            format_grammar(grammar).unwrap(),
+            "//! grammar doc\n// comment\n/// rule doc\na = {\n    \"a\" // trailing\n}\n\nb = { a }\n"
+        );
+    }
+}
@@ -0,0 +1,204 @@
+use pest::iterators::Pair;
+use pest_meta::parser::{self, Rule};
+
+/// The kind of a highlighted part of a grammar.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    /// a rule name (defined or referenced)
+    RuleName,
+    /// a string, case-insensitive string, character, range or number
+    Literal,
+    /// a prefix, infix or postfix operator
+    Operator,
+    /// a rule modifier (`_`, `@`, `$` or `!`) or a node tag
+    Modifier,
+    /// a built-in like `PUSH` or `PEEK[..]`
+    Keyword,
+    /// a comment (or doc comment)
+    Comment,
+    /// anything else (whitespace, braces, `=`, ...)
+    Plain,
+}
+
+impl Token {
+    /// Returns the CSS class of the token.
+    pub fn class(self) -> &'static str {
+        match self {
+            Token::RuleName => "token-rule",
+            Token::Literal => "token-literal",
+            Token::Operator => "token-operator",
+            Token::Modifier => "token-modifier",
+            Token::Keyword => "token-keyword",
+            Token::Comment => "token-comment",
+            Token::Plain => "token-plain",
+        }
+    }
+}
+
+/// Splits the grammar into highlighted parts, in order and covering all of it.
+/// A grammar that can't be parsed isn't highlighted (i.e. it's a single plain part).
+pub fn highlight(grammar: &str) -> Vec<(Token, &str)> {
+    let Ok(pairs) = parser::parse(Rule::grammar_rules, grammar) else {
+        return vec![(Token::Plain, grammar)];
+    };
+    let mut spans = vec![];
+    for pair in pairs {
+        collect(pair, &mut spans);
+    }
+    let mut parts = vec![];
+    let mut last_end = 0;
+    for (token, start, end) in spans {
+        gap(&grammar[last_end..start], &mut parts);
+        parts.push((token, &grammar[start..end]));
+        last_end = end;
+    }
+    gap(&grammar[last_end..], &mut parts);
+    parts
+}
+
+/// Collects the highlighted spans of the pair, i.e. of its innermost pairs
+/// (except for the literals, which are highlighted as a whole).
+fn collect(pair: Pair<Rule>, spans: &mut Vec<(Token, usize, usize)>) {
+    let token = match pair.as_rule() {
+        Rule::identifier => Token::RuleName,
+        Rule::string
+        | Rule::insensitive_string
+        | Rule::character
+        | Rule::range
+        | Rule::number
+        | Rule::integer => Token::Literal,
+        Rule::positive_predicate_operator
+        | Rule::negative_predicate_operator
+        | Rule::sequence_operator
+        | Rule::choice_operator
+        | Rule::optional_operator
+        | Rule::repeat_operator
+        | Rule::repeat_once_operator
+        | Rule::range_operator => Token::Operator,
+        Rule::silent_modifier
+        | Rule::atomic_modifier
+        | Rule::compound_atomic_modifier
+        | Rule::non_atomic_modifier
+        | Rule::tag_id => Token::Modifier,
+        Rule::grammar_doc | Rule::line_doc => Token::Comment,
+        _ => {
+            let mut inner = pair.clone().into_inner().peekable();
+            if inner.peek().is_none() {
+                let span = pair.as_span();
+                spans.push((Token::Plain, span.start(), span.end()));
+            }
+            for pair in inner {
+                collect(pair, spans);
+            }
+            return;
+        }
+    };
+    let span = pair.as_span();
+    spans.push((token, span.start(), span.end()));
+}
+
+/// Splits the text between two pairs into whitespace, comments
+/// and the keywords (which aren't pairs, e.g. `PUSH` of `PUSH(...)`).
+fn gap<'a>(mut text: &'a str, parts: &mut Vec<(Token, &'a str)>) {
+    while !text.is_empty() {
+        let len = if text.starts_with("//") {
+            text.find('\n').unwrap_or(text.len())
+        } else if text.starts_with("/*") {
+            // block comments can be nested
+            let mut depth = 0;
+            let mut end = text.len();
+            let mut i = 0;
+            while i < text.len() {
+                if text[i..].starts_with("/*") {
+                    depth += 1;
+                    i += 2;
+                } else if text[i..].starts_with("*/") {
+                    depth -= 1;
+                    i += 2;
+                    if depth == 0 {
+                        end = i;
+                        break;
+                    }
+                } else {
+                    i += text[i..].chars().next().map_or(1, char::len_utf8);
+                }
+            }
+            end
+        } else if text.starts_with(char::is_whitespace) {
+            text.find(|c: char| !c.is_whitespace())
+                .unwrap_or(text.len())
+        } else {
+            text.find(|c: char| c.is_whitespace() || c == '/')
+                .filter(|&len| len > 0)
+                .unwrap_or(text.len())
+        };
+        let token = if text.starts_with("//") || text.starts_with("/*") {
+            Token::Comment
+        } else if text.starts_with(char::is_whitespace) {
+            Token::Plain
+        } else {
+            Token::Keyword
+        };
+        parts.push((token, &text[..len]));
+        text = &text[len..];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{highlight, Token};
+
+    fn non_plain(grammar: &str) -> Vec<(Token, &str)> {
+        highlight(grammar)
+            .into_iter()
+            .filter(|(token, _)| *token != Token::Plain)
+            .collect()
+    }
+
+    #[test]
+    fn highlighted_rule() {
+        assert_eq!(
+            non_plain("// digits\nnum = @{ !\"0\" ~ ASCII_DIGIT+ | 'a'..'z' }"),
+            vec![
+                (Token::Comment, "// digits"),
+                (Token::RuleName, "num"),
+                (Token::Modifier, "@"),
+                (Token::Operator, "!"),
+                (Token::Literal, "\"0\""),
+                (Token::Operator, "~"),
+                (Token::RuleName, "ASCII_DIGIT"),
+                (Token::Operator, "+"),
+                (Token::Operator, "|"),
+                (Token::Literal, "'a'..'z'"),
+            ]
+        );
+    }
+
+    #[test]
+    fn highlighted_keywords_and_comments() {
+        assert_eq!(
+            non_plain("a = { PUSH(\"a\") /* x /* y */ */ ~ PEEK[..] }"),
+            vec![
+                (Token::RuleName, "a"),
+                (Token::Keyword, "PUSH"),
+                (Token::Literal, "\"a\""),
+                (Token::Comment, "/* x /* y */ */"),
+                (Token::Operator, "~"),
+                (Token::Keyword, "PEEK"),
+                (Token::Operator, ".."),
+            ]
+        );
+    }
+
+    #[test]
+    fn whole_grammar_is_covered() {
+        for grammar in ["a = { \"a\" }\n\n/// doc\nb = _{ a* }\n", "a = { ", ""] {
+            let text = highlight(grammar)
+                .into_iter()
+                .map(|(_, text)| text)
+                .collect::<String>();
+            assert_eq!(text, grammar);
+        }
+        assert_eq!(highlight("a = { "), vec![(Token::Plain, "a = { ")]);
+    }
+}
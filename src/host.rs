@@ -0,0 +1,92 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+/// A command the host page (i.e. the parent window of an embedded debugger)
+/// can post to control the debugger, e.g. `{"cmd": "run", "rule": "ident"}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "cmd", rename_all = "camelCase")]
+pub enum HostCommand {
+    /// replaces the grammar
+    LoadGrammar { text: String },
+    /// starts a debugger session on the rule
+    Run { rule: String },
+    /// continues to the next breakpoint
+    Continue,
+}
+
+impl HostCommand {
+    /// Reads a command from the data of a `message` event.
+    /// It returns `None` for the messages that aren't commands.
+    pub fn from_js(data: &JsValue) -> Option<Self> {
+        let json = js_sys::JSON::stringify(data).ok()?.as_string()?;
+        serde_json::from_str(&json).ok()
+    }
+}
+
+/// An event the debugger posts to the host page,
+/// e.g. `{"event": "breakpoint", "rule": "ident", "pos": 0}`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "camelCase")]
+pub enum HostEvent {
+    /// the debugger stopped at a breakpoint of the rule at the (byte) position
+    Breakpoint { rule: String, pos: usize },
+    /// the debugger session ended after a successful parse
+    Eof,
+    /// the grammar or parsing failed
+    Error { message: String },
+}
+
+impl HostEvent {
+    /// Posts the event to the parent window, if the debugger is embedded in one.
+    pub fn post(&self) -> Result<(), JsValue> {
+        let window = gloo_utils::window();
+        let Some(parent) = window.parent()? else {
+            return Ok(());
+        };
+        if parent == window {
+            return Ok(());
+        }
+        let json = serde_json::to_string(self).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        parent.post_message(&js_sys::JSON::parse(&json)?, "*")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HostCommand, HostEvent};
+
+    #[test]
+    fn message_schema() {
+        assert_eq!(
+            serde_json::from_str::<HostCommand>(
+                r#"{"cmd": "loadGrammar", "text": "a = { \"a\" }"}"#
+            )
+            .unwrap(),
+            HostCommand::LoadGrammar {
+                text: "a = { \"a\" }".to_owned()
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<HostCommand>(r#"{"cmd": "run", "rule": "a"}"#).unwrap(),
+            HostCommand::Run {
+                rule: "a".to_owned()
+            }
+        );
+        assert_eq!(
+            serde_json::from_str::<HostCommand>(r#"{"cmd": "continue"}"#).unwrap(),
+            HostCommand::Continue
+        );
+        assert_eq!(
+            serde_json::to_string(&HostEvent::Breakpoint {
+                rule: "a".to_owned(),
+                pos: 3
+            })
+            .unwrap(),
+            r#"{"event":"breakpoint","rule":"a","pos":3}"#
+        );
+        assert_eq!(
+            serde_json::to_string(&HostEvent::Eof).unwrap(),
+            r#"{"event":"eof"}"#
+        );
+    }
+}
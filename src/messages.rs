@@ -0,0 +1,562 @@
+use serde::{Deserialize, Serialize};
+
+/// The language of the UI.
+/// Only the UI is translated: the grammar, the input and the messages
+/// about them (e.g. the parsing errors) are shown as they are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Lang {
+    #[default]
+    English,
+    French,
+}
+
+impl Lang {
+    /// All the languages, in the order of the language selector.
+    pub const ALL: [Lang; 2] = [Lang::English, Lang::French];
+
+    /// The language tag (as in the `lang` HTML attribute).
+    pub fn code(self) -> &'static str {
+        match self {
+            Lang::English => "en",
+            Lang::French => "fr",
+        }
+    }
+
+    /// The language name (in the language itself).
+    pub fn name(self) -> &'static str {
+        match self {
+            Lang::English => "English",
+            Lang::French => "Français",
+        }
+    }
+
+    /// Returns the language with the tag, if it's supported.
+    pub fn from_code(code: &str) -> Option<Lang> {
+        Lang::ALL.into_iter().find(|lang| lang.code() == code)
+    }
+
+    /// The UI strings in the language.
+    pub fn messages(self) -> &'static Messages {
+        match self {
+            Lang::English => &ENGLISH,
+            Lang::French => &FRENCH,
+        }
+    }
+}
+
+/// The UI strings (i.e. labels, buttons, tooltips and placeholders).
+/// The strings completed with values (e.g. "Diagram of" and a rule name)
+/// are followed by them, while the messages with values in the middle
+/// are functions formatting them.
+pub struct Messages {
+    pub language: &'static str,
+    pub dark_theme: &'static str,
+    pub light_theme: &'static str,
+    pub grammar: &'static str,
+    pub fetch_grammar: &'static str,
+    pub format: &'static str,
+    pub indent: &'static str,
+    pub indent_spaces: &'static str,
+    pub indent_tab: &'static str,
+    pub clear: &'static str,
+    pub input_to_parse: &'static str,
+    pub resize_panes: &'static str,
+    pub maximize_pane: &'static str,
+    pub restore_pane: &'static str,
+    pub new_input: &'static str,
+    pub close_input: &'static str,
+    pub show_whitespace: &'static str,
+    pub normalize_line_endings: &'static str,
+    pub empty_input: &'static str,
+    pub copy: &'static str,
+    pub copy_title: &'static str,
+    pub copy_fallback: &'static str,
+    pub legend_attempted: &'static str,
+    pub legend_matched: &'static str,
+    pub legend_failed: &'static str,
+    pub legend_backtracked: &'static str,
+    pub download_tree: &'static str,
+    pub export_trace: &'static str,
+    pub export_trace_title: &'static str,
+    pub export_session: &'static str,
+    pub import_session: &'static str,
+    pub reset: &'static str,
+    pub copy_share_link: &'static str,
+    pub get_embed_code: &'static str,
+    pub get_embed_code_title: &'static str,
+    pub embed_width: &'static str,
+    pub embed_height: &'static str,
+    pub embed_copied: &'static str,
+    pub run: &'static str,
+    pub run_label: &'static str,
+    pub run_to_end: &'static str,
+    pub run_to_end_title: &'static str,
+    pub run_selection: &'static str,
+    pub run_selection_title: &'static str,
+    pub run_to_cursor: &'static str,
+    pub run_to_cursor_title: &'static str,
+    pub run_all: &'static str,
+    pub run_all_title: &'static str,
+    pub continue_run: &'static str,
+    pub continue_label: &'static str,
+    pub step_over: &'static str,
+    pub back: &'static str,
+    pub stop: &'static str,
+    pub stop_label: &'static str,
+    pub play: &'static str,
+    pub pause: &'static str,
+    pub add_all_breakpoints: &'static str,
+    pub remove_all_breakpoints: &'static str,
+    pub controls: &'static str,
+    pub dismiss: &'static str,
+    pub draft_found: &'static str,
+    pub restore: &'static str,
+    pub discard: &'static str,
+    pub parsed_successfully: &'static str,
+    pub clear_grammar_question: &'static str,
+    pub clear_input_question: &'static str,
+    pub clear_grammar_warning: &'static str,
+    pub clear_input_warning: &'static str,
+    pub cancel: &'static str,
+    pub error: &'static str,
+    pub close: &'static str,
+    pub expected: &'static str,
+    pub unexpected: &'static str,
+    pub show_in_grammar: &'static str,
+    pub grammar_errors: &'static str,
+    pub no_rules: &'static str,
+    pub select_rule: &'static str,
+    pub type_rule: &'static str,
+    pub type_rule_label: &'static str,
+    pub run_to_rule: &'static str,
+    pub run_to_rule_title: &'static str,
+    pub go_to_definition: &'static str,
+    pub nullable_title: &'static str,
+    pub window_from: &'static str,
+    pub window_to: &'static str,
+    pub window_skip: &'static str,
+    pub breakpoints: &'static str,
+    pub filter_rules: &'static str,
+    pub sort_title: &'static str,
+    pub grammar_order: &'static str,
+    pub alphabetical_order: &'static str,
+    pub skip_silent: &'static str,
+    pub skip_silent_title: &'static str,
+    pub coverage: &'static str,
+    pub coverage_rules: &'static str,
+    pub coverage_title: &'static str,
+    pub first_set_title: &'static str,
+    pub first_set_hint: &'static str,
+    pub attempts_per_rule: &'static str,
+    pub rule: &'static str,
+    pub attempts: &'static str,
+    pub timeline: &'static str,
+    pub depth: &'static str,
+    pub batch_run: &'static str,
+    pub batch_passed: &'static str,
+    pub input: &'static str,
+    pub result: &'static str,
+    pub select_input: &'static str,
+    pub failed: &'static str,
+    pub passed: &'static str,
+    pub parse_tree: &'static str,
+    pub rule_graph: &'static str,
+    pub diagram_of: &'static str,
+    pub saved_sessions: &'static str,
+    pub new_session: &'static str,
+    pub session_name: &'static str,
+    pub session_name_label: &'static str,
+    pub save: &'static str,
+    pub load: &'static str,
+    pub delete: &'static str,
+    pub grammar_ast: &'static str,
+    pub optimized: &'static str,
+    pub thanks: &'static str,
+    pub thanks_to: &'static str,
+    pub and: &'static str,
+    pub well: &'static str,
+    pub repo: &'static str,
+    pub input_name: fn(usize) -> String,
+    pub input_loaded: fn(&str, usize) -> String,
+    pub large_input_loaded: fn(&str, usize) -> String,
+    pub grammar_error_at: fn(usize, usize) -> String,
+    pub parsing_failed_at: fn(usize, usize) -> String,
+    pub partial_parse: fn(usize, usize, usize) -> String,
+    pub breakpoint_at: fn(usize, usize) -> String,
+    pub rule_matched_until: fn(&str, usize, usize) -> String,
+    pub rule_failed_at: fn(&str, usize, usize) -> String,
+    pub rule_backtracked: fn(&str, usize, usize, usize, usize) -> String,
+    pub announce_breakpoint: fn(&str, usize) -> String,
+    pub announce_matched: fn(&str, usize) -> String,
+    pub announce_failed: fn(&str, usize) -> String,
+    pub truncated: fn(usize, usize) -> String,
+    pub event_progress: fn(usize, usize) -> String,
+    pub parsed_in: fn(f64) -> String,
+    pub unreachable_from: fn(&str, &str) -> String,
+    pub breakpoint_on: fn(&str) -> String,
+    pub aborted: fn(usize) -> String,
+}
+
+pub const ENGLISH: Messages = Messages {
+    language: "Language",
+    dark_theme: "Dark theme",
+    light_theme: "Light theme",
+    grammar: "Grammar",
+    fetch_grammar: "Fetch grammar",
+    format: "Format",
+    indent: "Indentation inserted by the Tab key (Shift+Tab removes it)",
+    indent_spaces: "spaces",
+    indent_tab: "Tab",
+    clear: "Clear",
+    input_to_parse: "Input to parse",
+    resize_panes: "drag to resize the grammar and input panes",
+    maximize_pane: "Maximize",
+    restore_pane: "Restore (Esc)",
+    new_input: "new input",
+    close_input: "close",
+    show_whitespace: "show whitespace",
+    normalize_line_endings: "normalize line endings (CRLF to LF)",
+    empty_input: "␃ (empty input)",
+    copy: "Copy",
+    copy_title: "copy the rule and position (e.g. for an issue)",
+    copy_fallback: "The clipboard isn't available, copy it from here:",
+    legend_attempted: "attempted",
+    legend_matched: "matched",
+    legend_failed: "failed",
+    legend_backtracked: "consumed, then backtracked",
+    download_tree: "Download tree",
+    export_trace: "Export trace",
+    export_trace_title: "download the events of the run as a text log",
+    export_session: "Export session",
+    import_session: "Import session",
+    reset: "Reset",
+    copy_share_link: "Copy share link",
+    get_embed_code: "Get embed code",
+    get_embed_code_title:
+        "copy the HTML of an iframe showing this grammar and input (e.g. for a blog)",
+    embed_width: "width",
+    embed_height: "height",
+    embed_copied: "Copied to the clipboard:",
+    run: "Run",
+    run_label: "Run the selected rule",
+    run_to_end: "Run to end",
+    run_to_end_title: "run without stopping at the breakpoints",
+    run_selection: "Run selection",
+    run_selection_title: "run on the selected input text only",
+    run_to_cursor: "Run to cursor",
+    run_to_cursor_title: "run until a breakpoint is hit at or after the input cursor",
+    run_all: "Run all",
+    run_all_title: "run on every input tab",
+    continue_run: "Continue",
+    continue_label: "Continue to the next breakpoint",
+    step_over: "Step over",
+    back: "Back",
+    stop: "Stop",
+    stop_label: "Stop the debugger session",
+    play: "Play",
+    pause: "Pause",
+    add_all_breakpoints: "Add all breakpoints",
+    remove_all_breakpoints: "Remove all breakpoints",
+    controls: "Controls",
+    dismiss: "Dismiss",
+    draft_found: "Unsaved edits from a previous visit were found (e.g. before a crash or reload).",
+    restore: "Restore",
+    discard: "Discard",
+    parsed_successfully: "Input parsed successfully",
+    clear_grammar_question: "Clear the grammar?",
+    clear_input_question: "Clear the input?",
+    clear_grammar_warning: "The whole grammar will be deleted.",
+    clear_input_warning: "The whole input will be deleted.",
+    cancel: "Cancel",
+    error: "Error",
+    close: "Close",
+    expected: "expected:",
+    unexpected: "unexpected:",
+    show_in_grammar: "show in the grammar",
+    grammar_errors: "error(s) parsing the grammar",
+    no_rules: "The grammar has no rules to run: add one to start debugging.",
+    select_rule: "Select a rule to run",
+    type_rule: "or type a rule name",
+    type_rule_label: "Rule to run instead of the selected one",
+    run_to_rule: "Run to rule",
+    run_to_rule_title: "continue to the next breakpoint of the rule",
+    go_to_definition: "Go to definition",
+    nullable_title: "nullable: it can match without consuming any input",
+    window_from: "from",
+    window_to: "to",
+    window_skip: "skip",
+    breakpoints: "Breakpoints",
+    filter_rules: "filter rules",
+    sort_title: "toggle the order of the rules",
+    grammar_order: "Grammar order",
+    alphabetical_order: "A-Z",
+    skip_silent: "skip silent rules",
+    skip_silent_title: "don't stop at the breakpoints of silent (`_`) rules",
+    coverage: "coverage:",
+    coverage_rules: "rules",
+    coverage_title:
+        "the rules hit in the last run (only the rules with enabled breakpoints are tracked)",
+    first_set_title: "what a match of the rule can begin with (click a rule to show its set)",
+    first_set_hint: "click a rule to show its FIRST set",
+    attempts_per_rule: "Attempts per rule",
+    rule: "Rule",
+    attempts: "Attempts",
+    timeline: "Timeline",
+    depth: "depth",
+    batch_run: "Batch run:",
+    batch_passed: "passed",
+    input: "Input",
+    result: "Result",
+    select_input: "select the input",
+    failed: "✗ failed",
+    passed: "✓ passed",
+    parse_tree: "Parse tree",
+    rule_graph: "Rule graph",
+    diagram_of: "Diagram of",
+    saved_sessions: "Saved sessions",
+    new_session: "(new session)",
+    session_name: "session name",
+    session_name_label: "Session name",
+    save: "Save",
+    load: "Load",
+    delete: "Delete",
+    grammar_ast: "Grammar AST",
+    optimized: "optimized",
+    thanks: "Thanks",
+    thanks_to: "Thanks to",
+    and: "and",
+    well: "(well)",
+    repo: "Github repo",
+    input_name: |n| format!("Input {}", n),
+    input_loaded: |name, len| format!("{}: {} bytes loaded", name, len),
+    large_input_loaded: |name, len| {
+        format!(
+            "{}: {} bytes loaded (large inputs may be slow to highlight)",
+            name, len
+        )
+    },
+    grammar_error_at: |line, col| format!("✗ line {}, col {}", line, col),
+    parsing_failed_at: |line, col| format!("parsing failed at line {}, col {}", line, col),
+    partial_parse: |line, col, rest| {
+        format!(
+            "the input was only matched until line {}, col {}: {} bytes weren't consumed \
+             (end the rule with EOI to match the whole input)",
+            line, col, rest
+        )
+    },
+    breakpoint_at: |line, col| format!("at line {}, col {}", line, col),
+    rule_matched_until: |rule, line, col| {
+        format!("{} matched until line {}, col {}", rule, line, col)
+    },
+    rule_failed_at: |rule, line, col| format!("{} failed at line {}, col {}", rule, line, col),
+    rule_backtracked: |rule, line, col, end_line, end_col| {
+        format!(
+            "{} failed at line {}, col {} after advancing to line {}, col {}",
+            rule, line, col, end_line, end_col
+        )
+    },
+    announce_breakpoint: |rule, pos| format!("breakpoint at {}, position {}", rule, pos),
+    announce_matched: |rule, pos| format!("{} matched until position {}", rule, pos),
+    announce_failed: |rule, pos| format!("{} failed at position {}", rule, pos),
+    truncated: |shown, hit| format!("showing the first {} of {} breakpoints", shown, hit),
+    event_progress: |current, total| format!("Event {} / {}", current, total),
+    parsed_in: |time| format!("Parsed in {:.1} ms", time),
+    unreachable_from: |rule, rules| format!("unreachable from {}: {}", rule, rules),
+    breakpoint_on: |rule| format!("breakpoint on {}", rule),
+    aborted: |steps| {
+        format!(
+            "parsing aborted after {} steps: the grammar may backtrack excessively or not terminate",
+            steps
+        )
+    },
+};
+
+pub const FRENCH: Messages = Messages {
+    language: "Langue",
+    dark_theme: "Thème sombre",
+    light_theme: "Thème clair",
+    grammar: "Grammaire",
+    fetch_grammar: "Charger la grammaire",
+    format: "Formater",
+    indent: "Indentation insérée par la touche Tab (Maj+Tab la retire)",
+    indent_spaces: "espaces",
+    indent_tab: "Tabulation",
+    clear: "Effacer",
+    input_to_parse: "Entrée à analyser",
+    resize_panes: "faire glisser pour redimensionner la grammaire et l'entrée",
+    maximize_pane: "Agrandir",
+    restore_pane: "Restaurer (Échap)",
+    new_input: "nouvelle entrée",
+    close_input: "fermer",
+    show_whitespace: "afficher les espaces",
+    normalize_line_endings: "normaliser les fins de ligne (CRLF en LF)",
+    empty_input: "␃ (entrée vide)",
+    copy: "Copier",
+    copy_title: "copier la règle et la position (p. ex. pour un ticket)",
+    copy_fallback: "Le presse-papiers n'est pas disponible, copiez le texte ici :",
+    legend_attempted: "tentée",
+    legend_matched: "reconnue",
+    legend_failed: "échouée",
+    legend_backtracked: "consommée, puis abandonnée",
+    download_tree: "Télécharger l'arbre",
+    export_trace: "Exporter la trace",
+    export_trace_title: "télécharger les événements de l'exécution dans un journal texte",
+    export_session: "Exporter la session",
+    import_session: "Importer une session",
+    reset: "Réinitialiser",
+    copy_share_link: "Copier le lien de partage",
+    get_embed_code: "Obtenir le code d'intégration",
+    get_embed_code_title: "copier le HTML d'une iframe montrant cette grammaire et cette entrée (p. ex. pour un blog)",
+    embed_width: "largeur",
+    embed_height: "hauteur",
+    embed_copied: "Copié dans le presse-papiers :",
+    run: "Exécuter",
+    run_label: "Exécuter la règle sélectionnée",
+    run_to_end: "Exécuter jusqu'à la fin",
+    run_to_end_title: "exécuter sans s'arrêter aux points d'arrêt",
+    run_selection: "Exécuter la sélection",
+    run_selection_title: "exécuter seulement sur le texte sélectionné de l'entrée",
+    run_to_cursor: "Exécuter jusqu'au curseur",
+    run_to_cursor_title: "exécuter jusqu'à un point d'arrêt au curseur de l'entrée ou après",
+    run_all: "Tout exécuter",
+    run_all_title: "exécuter sur chaque onglet d'entrée",
+    continue_run: "Continuer",
+    continue_label: "Continuer jusqu'au prochain point d'arrêt",
+    step_over: "Passer",
+    back: "Retour",
+    stop: "Arrêter",
+    stop_label: "Arrêter la session de débogage",
+    play: "Lecture",
+    pause: "Pause",
+    add_all_breakpoints: "Ajouter tous les points d'arrêt",
+    remove_all_breakpoints: "Retirer tous les points d'arrêt",
+    controls: "Contrôles",
+    dismiss: "Fermer",
+    draft_found: "Des modifications non enregistrées d'une visite précédente ont été trouvées (p. ex. avant un plantage ou un rechargement).",
+    restore: "Restaurer",
+    discard: "Ignorer",
+    parsed_successfully: "Entrée analysée avec succès",
+    clear_grammar_question: "Effacer la grammaire ?",
+    clear_input_question: "Effacer l'entrée ?",
+    clear_grammar_warning: "Toute la grammaire sera supprimée.",
+    clear_input_warning: "Toute l'entrée sera supprimée.",
+    cancel: "Annuler",
+    error: "Erreur",
+    close: "Fermer",
+    expected: "attendu :",
+    unexpected: "inattendu :",
+    show_in_grammar: "afficher dans la grammaire",
+    grammar_errors: "erreur(s) d'analyse de la grammaire",
+    no_rules: "La grammaire n'a aucune règle à exécuter : ajoutez-en une pour commencer le débogage.",
+    select_rule: "Choisissez une règle à exécuter",
+    type_rule: "ou tapez un nom de règle",
+    type_rule_label: "Règle à exécuter au lieu de celle sélectionnée",
+    run_to_rule: "Exécuter jusqu'à la règle",
+    run_to_rule_title: "continuer jusqu'au prochain point d'arrêt de la règle",
+    go_to_definition: "Aller à la définition",
+    nullable_title: "annulable : elle peut réussir sans consommer d'entrée",
+    window_from: "de",
+    window_to: "à",
+    window_skip: "ignorer",
+    breakpoints: "Points d'arrêt",
+    filter_rules: "filtrer les règles",
+    sort_title: "changer l'ordre des règles",
+    grammar_order: "Ordre de la grammaire",
+    alphabetical_order: "A-Z",
+    skip_silent: "ignorer les règles silencieuses",
+    skip_silent_title: "ne pas s'arrêter aux points d'arrêt des règles silencieuses (`_`)",
+    coverage: "couverture :",
+    coverage_rules: "règles",
+    coverage_title: "les règles atteintes lors de la dernière exécution (seules les règles avec un point d'arrêt actif sont suivies)",
+    first_set_title: "ce par quoi une correspondance de la règle peut commencer (cliquez sur une règle pour afficher son ensemble)",
+    first_set_hint: "cliquez sur une règle pour afficher son ensemble FIRST",
+    attempts_per_rule: "Tentatives par règle",
+    rule: "Règle",
+    attempts: "Tentatives",
+    timeline: "Chronologie",
+    depth: "profondeur",
+    batch_run: "Exécution groupée :",
+    batch_passed: "réussies",
+    input: "Entrée",
+    result: "Résultat",
+    select_input: "sélectionner l'entrée",
+    failed: "✗ échec",
+    passed: "✓ réussite",
+    parse_tree: "Arbre syntaxique",
+    rule_graph: "Graphe des règles",
+    diagram_of: "Diagramme de",
+    saved_sessions: "Sessions enregistrées",
+    new_session: "(nouvelle session)",
+    session_name: "nom de la session",
+    session_name_label: "Nom de la session",
+    save: "Enregistrer",
+    load: "Charger",
+    delete: "Supprimer",
+    grammar_ast: "AST de la grammaire",
+    optimized: "optimisé",
+    thanks: "Merci",
+    thanks_to: "Merci à",
+    and: "et",
+    well: "(enfin)",
+    repo: "Dépôt Github",
+    input_name: |n| format!("Entrée {}", n),
+    input_loaded: |name, len| format!("{} : {} octets chargés", name, len),
+    large_input_loaded: |name, len| {
+        format!(
+            "{} : {} octets chargés (la coloration des grandes entrées peut être lente)",
+            name, len
+        )
+    },
+    grammar_error_at: |line, col| format!("✗ ligne {}, col. {}", line, col),
+    parsing_failed_at: |line, col| format!("échec de l'analyse à la ligne {}, col. {}", line, col),
+    partial_parse: |line, col, rest| {
+        format!(
+            "l'entrée n'a été reconnue que jusqu'à la ligne {}, col. {} : {} octets n'ont pas été \
+             consommés (terminez la règle par EOI pour reconnaître toute l'entrée)",
+            line, col, rest
+        )
+    },
+    breakpoint_at: |line, col| format!("à la ligne {}, col. {}", line, col),
+    rule_matched_until: |rule, line, col| {
+        format!("{} reconnue jusqu'à la ligne {}, col. {}", rule, line, col)
+    },
+    rule_failed_at: |rule, line, col| format!("{} échouée à la ligne {}, col. {}", rule, line, col),
+    rule_backtracked: |rule, line, col, end_line, end_col| {
+        format!(
+            "{} échouée à la ligne {}, col. {} après avoir avancé jusqu'à la ligne {}, col. {}",
+            rule, line, col, end_line, end_col
+        )
+    },
+    announce_breakpoint: |rule, pos| format!("point d'arrêt sur {}, position {}", rule, pos),
+    announce_matched: |rule, pos| format!("{} reconnue jusqu'à la position {}", rule, pos),
+    announce_failed: |rule, pos| format!("{} échouée à la position {}", rule, pos),
+    truncated: |shown, hit| {
+        format!("affichage des {} premiers points d'arrêt sur {}", shown, hit)
+    },
+    event_progress: |current, total| format!("Événement {} / {}", current, total),
+    parsed_in: |time| format!("Analysé en {:.1} ms", time),
+    unreachable_from: |rule, rules| format!("inaccessibles depuis {} : {}", rule, rules),
+    breakpoint_on: |rule| format!("point d'arrêt sur {}", rule),
+    aborted: |steps| {
+        format!(
+            "analyse interrompue après {} étapes : la grammaire fait peut-être trop de retours \
+             en arrière ou ne termine pas",
+            steps
+        )
+    },
+};
+
+#[cfg(test)]
+mod tests {
+    use super::Lang;
+
+    #[test]
+    fn language_codes() {
+        for lang in Lang::ALL {
+            assert_eq!(Lang::from_code(lang.code()), Some(lang));
+        }
+        assert_eq!(Lang::from_code("xx"), None);
+        assert_eq!(Lang::French.messages().run, "Exécuter");
+        assert_eq!((Lang::French.messages().input_name)(2), "Entrée 2");
+    }
+}
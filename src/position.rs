@@ -0,0 +1,569 @@
+/// Returns the closest char boundary at or before the `idx` byte offset.
+pub fn char_boundary(input: &str, idx: usize) -> usize {
+    let mut idx = idx.min(input.len());
+    while !input.is_char_boundary(idx) {
+        idx -= 1;
+    }
+    idx
+}
+
+/// Splits the input at the `idx` byte offset (as reported by pest)
+/// into the text before, the character at and the text after the offset.
+pub fn split_at_char(input: &str, idx: usize) -> (&str, &str, &str) {
+    let idx = char_boundary(input, idx);
+    let (start, rest) = input.split_at(idx);
+    let len = rest.chars().next().map_or(0, char::len_utf8);
+    let (current, rest) = rest.split_at(len);
+    (start, current, rest)
+}
+
+/// Splits the input into the text before, within and after
+/// the `start..end` byte range.
+/// If the range is empty, the character at `start` is used instead.
+pub fn split_at_span(input: &str, start: usize, end: usize) -> (&str, &str, &str) {
+    let start = char_boundary(input, start);
+    let end = char_boundary(input, end);
+    if end <= start {
+        return split_at_char(input, start);
+    }
+    (&input[..start], &input[start..end], &input[end..])
+}
+
+/// Like `split_at_char`, but only returns the line containing the `idx` byte offset
+/// (without its line break, unless the offset points at it).
+pub fn split_line_at_char(input: &str, idx: usize) -> (&str, &str, &str) {
+    let idx = char_boundary(input, idx);
+    let line_start = input[..idx].rfind(['\r', '\n']).map_or(0, |i| i + 1);
+    let (before, current, after) = split_at_char(&input[line_start..], idx - line_start);
+    let line_end = if current.starts_with(['\r', '\n']) {
+        0
+    } else {
+        after.find(['\r', '\n']).unwrap_or(after.len())
+    };
+    (before, current, &after[..line_end])
+}
+
+/// Returns the 1-based (line, column) of the character at the `idx` byte offset.
+/// `\r\n`, lone `\n` and lone `\r` are all counted as a single line break.
+/// Columns are counted in characters.
+pub fn line_col(input: &str, idx: usize) -> (usize, usize) {
+    let idx = char_boundary(input, idx);
+    let mut line = 1;
+    let mut col = 1;
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if i >= idx {
+            break;
+        }
+        match c {
+            // the line break of a "\r\n" is counted on the '\n'
+            '\r' if matches!(chars.peek(), Some((_, '\n'))) => col += 1,
+            '\r' | '\n' => {
+                line += 1;
+                col = 1;
+            }
+            _ => col += 1,
+        }
+    }
+    (line, col)
+}
+
+/// Returns the byte offset of the character at the 1-based (line, column),
+/// i.e. the inverse of `line_col`.
+/// Positions past the end of a line or of the input are clamped.
+pub fn byte_offset(input: &str, line: usize, col: usize) -> usize {
+    let (mut cur_line, mut cur_col) = (1, 1);
+    let mut chars = input.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        if cur_line > line || (cur_line == line && cur_col >= col) {
+            return i;
+        }
+        match c {
+            '\r' if matches!(chars.peek(), Some((_, '\n'))) => cur_col += 1,
+            '\r' | '\n' if cur_line == line => return i,
+            '\r' | '\n' => {
+                cur_line += 1;
+                cur_col = 1;
+            }
+            _ => cur_col += 1,
+        }
+    }
+    input.len()
+}
+
+/// Returns the byte range of the line containing the `idx` byte offset
+/// (without its line break).
+pub fn line_span(input: &str, idx: usize) -> (usize, usize) {
+    let idx = char_boundary(input, idx);
+    let start = input[..idx].rfind(['\r', '\n']).map_or(0, |i| i + 1);
+    let end = input[idx..]
+        .find(['\r', '\n'])
+        .map_or(input.len(), |i| idx + i);
+    (start, end)
+}
+
+/// Returns the byte range of a rule's definition head (`rule = _{`) in a grammar,
+/// i.e. a line starting with the rule name, followed by `=`,
+/// an optional `_`/`@`/`!`/`$` modifier and `{`.
+pub fn rule_definition(grammar: &str, rule: &str) -> Option<(usize, usize)> {
+    let mut line_start = 0;
+    for line in grammar.split_inclusive('\n') {
+        let indent = line.len() - line.trim_start().len();
+        let start = line_start + indent;
+        line_start += line.len();
+        let Some(rest) = line[indent..].strip_prefix(rule) else {
+            continue;
+        };
+        // also skips the rules whose names merely start with the rule name
+        let Some(after_eq) = rest.trim_start().strip_prefix('=') else {
+            continue;
+        };
+        let after_eq = after_eq.trim_start();
+        let after_modifier = after_eq
+            .strip_prefix(['_', '@', '!', '$'])
+            .unwrap_or(after_eq)
+            .trim_start();
+        if after_modifier.starts_with('{') {
+            let end = start + (line.len() - indent) - after_modifier.len() + 1;
+            return Some((start, end));
+        }
+    }
+    None
+}
+
+/// Converts the `idx` byte offset to an offset in UTF-16 code units
+/// (as used by the DOM APIs).
+pub fn utf16_offset(input: &str, idx: usize) -> usize {
+    input[..char_boundary(input, idx)].encode_utf16().count()
+}
+
+/// Converts an offset in UTF-16 code units (as used by the DOM APIs)
+/// to a byte offset, i.e. the inverse of `utf16_offset`.
+/// Offsets in the middle of a character snap forward to its end.
+pub fn byte_offset_from_utf16(input: &str, idx: usize) -> usize {
+    let mut units = 0;
+    for (i, c) in input.char_indices() {
+        if units >= idx {
+            return i;
+        }
+        units += c.len_utf16();
+    }
+    input.len()
+}
+
+/// Returns a one-line snippet of the input around the `idx` byte offset,
+/// with up to `radius` characters on each side of a `|` marking the offset
+/// (e.g. `…hel|lo…`). The line breaks are shown as `␊`.
+pub fn snippet(input: &str, idx: usize, radius: usize) -> String {
+    let idx = char_boundary(input, idx);
+    let (before, after) = input.split_at(idx);
+    let start = match radius {
+        0 => idx,
+        _ => before
+            .char_indices()
+            .rev()
+            .nth(radius - 1)
+            .map_or(0, |(i, _)| i),
+    };
+    let end = after
+        .char_indices()
+        .nth(radius)
+        .map_or(after.len(), |(i, _)| i);
+    let ellipsis = |cut: bool| if cut { "…" } else { "" };
+    let one_line = |text: &str| text.replace("\r\n", "␊").replace(['\n', '\r'], "␊");
+    format!(
+        "{}{}|{}{}",
+        ellipsis(start > 0),
+        one_line(&before[start..]),
+        one_line(&after[..end]),
+        ellipsis(end < after.len())
+    )
+}
+
+/// Replaces the spaces, tabs and newlines of the text with visible glyphs
+/// (`·`, `→` and `¶`). The newlines are kept after their glyphs,
+/// so that the text still has the same lines.
+pub fn show_whitespace(text: &str) -> String {
+    text.replace(' ', "·")
+        .replace('\t', "→")
+        .replace('\n', "¶\n")
+}
+
+/// Describes the length of the input and, if known, the position of the caret
+/// at the `caret` byte offset (as pest reports the positions in bytes).
+pub fn input_status(input: &str, caret: Option<usize>) -> String {
+    let length = format!("{} chars, {} bytes", input.chars().count(), input.len());
+    match caret {
+        Some(caret) => {
+            let caret = char_boundary(input, caret);
+            let (line, col) = line_col(input, caret);
+            format!(
+                "{} | caret at byte {} (line {}, column {}), {} bytes remaining",
+                length,
+                caret,
+                line,
+                col,
+                input.len() - caret
+            )
+        }
+        None => length,
+    }
+}
+
+/// The replacement of the `start..end` bytes of a text with `text`,
+/// and the byte range selected afterwards.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TextEdit {
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub selection: (usize, usize),
+}
+
+/// Returns the start of the line of `start` and the starts of the following lines
+/// up to `end`, i.e. the lines a selection of `start..end` spans.
+/// (A line starting at the end of a selection isn't part of it.)
+fn selected_lines(text: &str, start: usize, end: usize) -> Vec<usize> {
+    let first = text[..start].rfind('\n').map_or(0, |i| i + 1);
+    std::iter::once(first)
+        .chain(
+            text[first..end]
+                .match_indices('\n')
+                .map(|(i, _)| first + i + 1)
+                .filter(|&line| line < end),
+        )
+        .collect()
+}
+
+/// Builds the edit applying the `(position, removed length, inserted text)` changes
+/// (in order, not overlapping) to the text, and moves the `start..end` selection with them.
+fn apply_changes(
+    text: &str,
+    start: usize,
+    end: usize,
+    changes: &[(usize, usize, &str)],
+) -> TextEdit {
+    let (Some(first), Some(last)) = (changes.first(), changes.last()) else {
+        return TextEdit {
+            start,
+            end: start,
+            text: String::new(),
+            selection: (start, end),
+        };
+    };
+    let edit_end = end.max(last.0 + last.1);
+    let mut replacement = String::new();
+    let mut copied = first.0;
+    for (pos, removed, inserted) in changes {
+        replacement.push_str(&text[copied..*pos]);
+        replacement.push_str(inserted);
+        copied = pos + removed;
+    }
+    replacement.push_str(&text[copied..edit_end]);
+    // the text inserted at a position goes before a selection boundary there,
+    // except at the start of the selection
+    let moved = |offset: usize, at_start: bool| {
+        changes
+            .iter()
+            .fold(offset as isize, |moved, (pos, removed, inserted)| {
+                if *pos > offset || (*pos == offset && (at_start || *removed > 0)) {
+                    moved
+                } else {
+                    let removed = (*removed).min(offset - pos);
+                    moved - removed as isize + inserted.len() as isize
+                }
+            }) as usize
+    };
+    let selection_start = moved(start, true);
+    TextEdit {
+        start: first.0,
+        end: edit_end,
+        text: replacement,
+        selection: (
+            selection_start,
+            moved(end, start == end).max(selection_start),
+        ),
+    }
+}
+
+/// Returns the edit indenting the `start..end` selection with `unit` (e.g. a tab):
+/// the selected text is replaced with it, unless the selection spans several lines,
+/// which are all indented.
+pub fn indent(text: &str, start: usize, end: usize, unit: &str) -> TextEdit {
+    if !text[start..end].contains('\n') {
+        return TextEdit {
+            start,
+            end,
+            text: unit.to_owned(),
+            selection: (start + unit.len(), start + unit.len()),
+        };
+    }
+    let changes = selected_lines(text, start, end)
+        .into_iter()
+        .map(|line| (line, 0, unit))
+        .collect::<Vec<_>>();
+    apply_changes(text, start, end, &changes)
+}
+
+/// Returns the edit removing a level of indentation (a tab or up to `width` spaces)
+/// from the lines of the `start..end` selection (or of the caret).
+pub fn dedent(text: &str, start: usize, end: usize, width: usize) -> TextEdit {
+    let changes = selected_lines(text, start, end)
+        .into_iter()
+        .filter_map(|line| {
+            let rest = &text[line..];
+            let removed = if rest.starts_with('\t') {
+                1
+            } else {
+                rest.bytes().take(width).take_while(|b| *b == b' ').count()
+            };
+            (removed > 0).then_some((line, removed, ""))
+        })
+        .collect::<Vec<_>>();
+    apply_changes(text, start, end, &changes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        byte_offset, byte_offset_from_utf16, dedent, indent, input_status, line_col, line_span,
+        rule_definition, show_whitespace, snippet, split_at_char, split_at_span,
+        split_line_at_char, utf16_offset,
+    };
+
+    #[test]
+    fn single_line() {
+        assert_eq!(line_col("hello world", 0), (1, 1));
+        assert_eq!(line_col("hello world", 6), (1, 7));
+        assert_eq!(line_col("hello world", 11), (1, 12));
+    }
+
+    #[test]
+    fn line_feed() {
+        let input = "ab\ncd\n\nef";
+        assert_eq!(line_col(input, 2), (1, 3));
+        assert_eq!(line_col(input, 3), (2, 1));
+        assert_eq!(line_col(input, 4), (2, 2));
+        assert_eq!(line_col(input, 6), (3, 1));
+        assert_eq!(line_col(input, 7), (4, 1));
+    }
+
+    #[test]
+    fn carriage_return_line_feed() {
+        let input = "ab\r\ncd";
+        assert_eq!(line_col(input, 2), (1, 3));
+        assert_eq!(line_col(input, 3), (1, 4));
+        assert_eq!(line_col(input, 4), (2, 1));
+        assert_eq!(line_col(input, 5), (2, 2));
+    }
+
+    #[test]
+    fn lone_carriage_return() {
+        let input = "ab\rcd\r\r";
+        assert_eq!(line_col(input, 3), (2, 1));
+        assert_eq!(line_col(input, 6), (3, 1));
+        assert_eq!(line_col(input, 7), (4, 1));
+    }
+
+    #[test]
+    fn mixed_line_endings() {
+        let input = "a\r\nb\nc\rd";
+        assert_eq!(line_col(input, 3), (2, 1));
+        assert_eq!(line_col(input, 5), (3, 1));
+        assert_eq!(line_col(input, 7), (4, 1));
+    }
+
+    #[test]
+    fn multi_byte_line_col() {
+        let input = "héllo\nwörld";
+        // 'l' after the two-byte 'é'
+        assert_eq!(line_col(input, 3), (1, 3));
+        // 'w' after the newline
+        assert_eq!(line_col(input, 7), (2, 1));
+        assert_eq!(line_col(input, 10), (2, 3));
+    }
+
+    #[test]
+    fn split_ascii() {
+        assert_eq!(split_at_char("hello", 1), ("h", "e", "llo"));
+        assert_eq!(split_at_char("hello", 5), ("hello", "", ""));
+    }
+
+    #[test]
+    fn split_accented() {
+        let input = "héllo";
+        assert_eq!(split_at_char(input, 1), ("h", "é", "llo"));
+        assert_eq!(split_at_char(input, 3), ("hé", "l", "lo"));
+    }
+
+    #[test]
+    fn split_emoji() {
+        let input = "a🦀b😀";
+        assert_eq!(split_at_char(input, 1), ("a", "🦀", "b😀"));
+        assert_eq!(split_at_char(input, 5), ("a🦀", "b", "😀"));
+        assert_eq!(split_at_char(input, 6), ("a🦀b", "😀", ""));
+        assert_eq!(split_at_char(input, 10), ("a🦀b😀", "", ""));
+    }
+
+    #[test]
+    fn split_not_on_boundary() {
+        // offsets in the middle of a character snap back to its start
+        assert_eq!(split_at_char("a🦀b", 3), ("a", "🦀", "b"));
+    }
+
+    #[test]
+    fn empty_input() {
+        assert_eq!(split_at_char("", 0), ("", "", ""));
+        assert_eq!(split_at_span("", 0, 0), ("", "", ""));
+        assert_eq!(split_line_at_char("", 0), ("", "", ""));
+        assert_eq!(line_col("", 0), (1, 1));
+        assert_eq!(byte_offset("", 1, 1), 0);
+        assert_eq!(
+            input_status("", Some(0)),
+            "0 chars, 0 bytes | caret at byte 0 (line 1, column 1), 0 bytes remaining"
+        );
+    }
+
+    #[test]
+    fn split_span() {
+        assert_eq!(split_at_span("hello world", 0, 5), ("", "hello", " world"));
+        assert_eq!(split_at_span("héllo wörld", 7, 13), ("héllo ", "wörld", ""));
+        // empty spans fall back to a single character
+        assert_eq!(split_at_span("hello", 1, 1), ("h", "e", "llo"));
+    }
+
+    #[test]
+    fn split_line() {
+        let input = "ab\r\ncd\nef";
+        assert_eq!(split_line_at_char(input, 1), ("a", "b", ""));
+        assert_eq!(split_line_at_char(input, 5), ("c", "d", ""));
+        assert_eq!(split_line_at_char(input, 7), ("", "e", "f"));
+        // the line break itself is kept when pointed at
+        assert_eq!(split_line_at_char(input, 6), ("cd", "\n", ""));
+        assert_eq!(split_line_at_char(input, 9), ("ef", "", ""));
+    }
+
+    #[test]
+    fn line_col_round_trip() {
+        let input = "héllo\r\nwörld\n\n!";
+        for idx in (0..=input.len()).filter(|idx| input.is_char_boundary(*idx)) {
+            let (line, col) = line_col(input, idx);
+            assert_eq!(byte_offset(input, line, col), idx);
+        }
+        // past the end of a line (at the '\n' of its "\r\n") or the input
+        assert_eq!(byte_offset(input, 1, 20), 7);
+        assert_eq!(byte_offset(input, 10, 1), input.len());
+    }
+
+    #[test]
+    fn span_of_line() {
+        let input = "a = { b }\r\nb = { \"b\" }\n";
+        assert_eq!(line_span(input, 0), (0, 9));
+        assert_eq!(line_span(input, 11), (11, 22));
+        assert_eq!(line_span(input, 15), (11, 22));
+        assert_eq!(line_span(input, input.len()), (input.len(), input.len()));
+        assert_eq!(line_span("", 0), (0, 0));
+    }
+
+    #[test]
+    fn find_rule_definition() {
+        let grammar = "alpha = { 'a'..'z' }\nalphas =@{ alpha+ }\n  digit= _ {'0'..'9'}";
+        assert_eq!(rule_definition(grammar, "alpha"), Some((0, 9)));
+        assert_eq!(rule_definition(grammar, "alphas"), Some((21, 31)));
+        assert_eq!(rule_definition(grammar, "digit"), Some((43, 53)));
+        assert_eq!(rule_definition(grammar, "alph"), None);
+        assert_eq!(rule_definition(grammar, "ident"), None);
+    }
+
+    #[test]
+    fn utf16() {
+        assert_eq!(utf16_offset("héllo", 3), 2);
+        assert_eq!(utf16_offset("a🦀b", 5), 3);
+    }
+
+    #[test]
+    fn utf16_round_trip() {
+        let input = "a🦀é\r\nb";
+        for idx in (0..=input.len()).filter(|idx| input.is_char_boundary(*idx)) {
+            assert_eq!(byte_offset_from_utf16(input, utf16_offset(input, idx)), idx);
+        }
+        // within the surrogate pair of the emoji
+        assert_eq!(byte_offset_from_utf16(input, 2), 5);
+        assert_eq!(byte_offset_from_utf16(input, 100), input.len());
+    }
+
+    #[test]
+    fn visible_whitespace() {
+        assert_eq!(show_whitespace("a b\tc\r\nd"), "a·b→c\r¶\nd");
+        assert_eq!(show_whitespace("abc"), "abc");
+    }
+
+    #[test]
+    fn status_of_input() {
+        assert_eq!(input_status("aé\nb", None), "4 chars, 5 bytes");
+        assert_eq!(
+            input_status("aé\nb", Some(4)),
+            "4 chars, 5 bytes | caret at byte 4 (line 2, column 1), 1 bytes remaining"
+        );
+        // within the 'é'
+        assert_eq!(
+            input_status("aé\nb", Some(2)),
+            "4 chars, 5 bytes | caret at byte 1 (line 1, column 2), 4 bytes remaining"
+        );
+    }
+
+    #[test]
+    fn indented_selection() {
+        // the caret or a selection within a line is replaced
+        let edit = indent("a = { b }", 6, 7, "  ");
+        assert_eq!((edit.start, edit.end, edit.text.as_str()), (6, 7, "  "));
+        assert_eq!(edit.selection, (8, 8));
+        // the lines of a selection are indented, except one it ends at the start of
+        let text = "a\nbc\nd";
+        let edit = indent(text, 1, 5, "\t");
+        assert_eq!(
+            (edit.start, edit.end, edit.text.as_str()),
+            (0, 5, "\ta\n\tbc\n")
+        );
+        assert_eq!(edit.selection, (2, 7));
+        let edit = indent(text, 2, 6, "  ");
+        assert_eq!(
+            (edit.start, edit.end, edit.text.as_str()),
+            (2, 6, "  bc\n  d")
+        );
+        assert_eq!(edit.selection, (2, 10));
+    }
+
+    #[test]
+    fn dedented_selection() {
+        let text = "    a\n\tb\n  c\nd";
+        let edit = dedent(text, 2, 2, 4);
+        // the caret was in the removed indentation
+        assert_eq!((edit.start, edit.end, edit.text.as_str()), (0, 4, ""));
+        assert_eq!(edit.selection, (0, 0));
+        let edit = dedent(text, 5, 12, 4);
+        assert_eq!(
+            (edit.start, edit.end, edit.text.as_str()),
+            (0, 12, "a\nb\nc")
+        );
+        assert_eq!(edit.selection, (1, 5));
+        // only up to the indentation width
+        assert_eq!(dedent("      a", 7, 7, 4).selection, (3, 3));
+        // nothing to remove
+        let edit = dedent(text, 14, 14, 4);
+        assert_eq!((edit.start, edit.end, edit.text.as_str()), (14, 14, ""));
+        assert_eq!(edit.selection, (14, 14));
+    }
+
+    #[test]
+    fn input_snippet() {
+        assert_eq!(snippet("hello", 3, 10), "hel|lo");
+        assert_eq!(snippet("0123456789abcdefghij", 10, 3), "…789|abc…");
+        // a "\r\n" counts as two characters
+        assert_eq!(snippet("ab\r\ncd", 4, 3), "…b␊|cd");
+        // the offsets are bytes, the radius characters
+        assert_eq!(snippet("żółw jedzie", 6, 2), "…ół|w …");
+        assert_eq!(snippet("", 0, 3), "|");
+        assert_eq!(snippet("abc", 3, 0), "…|");
+    }
+}
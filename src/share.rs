@@ -0,0 +1,119 @@
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::JsValue;
+
+use crate::app::Breakpoint;
+
+/// The part of the debugger state that is shared via the URL hash.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SharedState {
+    /// the grammar text
+    pub grammar: String,
+    /// the input text
+    pub input: String,
+    /// the rule to run
+    pub to_run: String,
+}
+
+impl SharedState {
+    /// Encodes the state as base64-encoded JSON.
+    /// `btoa` only accepts Latin-1 strings, so the UTF-8 bytes are passed as such.
+    pub fn encode(&self) -> Result<String, JsValue> {
+        let json = serde_json::to_string(self).map_err(|e| JsValue::from_str(&e.to_string()))?;
+        let binary = json.bytes().map(char::from).collect::<String>();
+        gloo_utils::window().btoa(&binary)
+    }
+
+    /// Decodes the state from base64-encoded JSON.
+    pub fn decode(encoded: &str) -> Option<Self> {
+        let binary = gloo_utils::window().atob(encoded).ok()?;
+        let bytes = binary
+            .chars()
+            .map(|c| u8::try_from(c).ok())
+            .collect::<Option<Vec<_>>>()?;
+        serde_json::from_slice(&bytes).ok()
+    }
+
+    /// Reads the state from the page URL hash, if there's any.
+    pub fn from_location() -> Option<Self> {
+        let hash = gloo_utils::window().location().hash().ok()?;
+        Self::decode(hash.strip_prefix('#')?)
+    }
+
+    /// Replaces the page URL hash with the encoded state
+    /// (without adding a browser history entry).
+    pub fn update_location(&self) -> Result<(), JsValue> {
+        let hash = format!("#{}", self.encode()?);
+        gloo_utils::history().replace_state_with_url(&JsValue::NULL, "", Some(&hash))
+    }
+
+    /// Returns the page URL with the encoded state.
+    pub fn link(&self) -> Result<String, JsValue> {
+        let location = gloo_utils::window().location();
+        Ok(format!(
+            "{}{}{}#{}",
+            location.origin()?,
+            location.pathname()?,
+            location.search()?,
+            self.encode()?
+        ))
+    }
+}
+
+/// Escapes the text for an HTML attribute value.
+fn escape_attribute(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Returns the HTML of an iframe embedding the page at the link
+/// (e.g. a share link) in a blog post or other web page.
+pub fn embed_code(link: &str, title: &str, width: u32, height: u32) -> String {
+    format!(
+        "<iframe src=\"{}\" title=\"{}\" width=\"{}\" height=\"{}\" style=\"border: 0\"></iframe>",
+        escape_attribute(link),
+        escape_attribute(title),
+        width,
+        height
+    )
+}
+
+/// A full debugger session that can be exported and imported as JSON.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// the grammar text
+    pub grammar: String,
+    /// the input text (of the active input tab)
+    pub input: String,
+    /// the input tabs as (name, content)
+    #[serde(default)]
+    pub inputs: Vec<(String, String)>,
+    /// the index of the active input tab
+    #[serde(default)]
+    pub active_input: usize,
+    /// the breakpoints (including the disabled ones)
+    pub breakpoints: Vec<Breakpoint>,
+    /// the rule to run
+    pub to_run: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::embed_code;
+
+    #[test]
+    fn iframe_embed_code() {
+        assert_eq!(
+            embed_code(
+                "https://example.com/?a=1&b=2#e30=",
+                "\"pest\" <debugger>",
+                800,
+                600
+            ),
+            "<iframe src=\"https://example.com/?a=1&amp;b=2#e30=\" \
+             title=\"&quot;pest&quot; &lt;debugger&gt;\" width=\"800\" height=\"600\" \
+             style=\"border: 0\"></iframe>"
+        );
+    }
+}
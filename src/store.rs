@@ -0,0 +1,239 @@
+use std::{cell::RefCell, rc::Rc};
+
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{
+    DomException, IdbDatabase, IdbObjectStore, IdbRequest, IdbTransaction, IdbTransactionMode,
+};
+use yew::Callback;
+
+use crate::share::Session;
+
+/// The IndexedDB database of the saved sessions.
+const DB_NAME: &str = "pest-web-debug";
+/// The version of the database (i.e. of its object stores).
+const DB_VERSION: u32 = 1;
+/// The object store of the saved sessions:
+/// their JSON (as exported) keyed by their names.
+const STORE: &str = "sessions";
+
+/// Describes a storage error from the name and message of its `DOMException`.
+fn describe_error(name: &str, message: &str) -> String {
+    match name {
+        "QuotaExceededError" => {
+            "the browser storage is full: delete some saved sessions and try again".to_owned()
+        }
+        "InvalidStateError" | "SecurityError" | "UnknownError" => format!(
+            "the browser storage is unavailable (e.g. in a private window): {}",
+            message
+        ),
+        _ => format!("the browser storage failed: {}", message),
+    }
+}
+
+fn exception_message(exception: Option<DomException>) -> String {
+    match exception {
+        Some(exception) => describe_error(&exception.name(), &exception.message()),
+        None => describe_error("", "unknown error"),
+    }
+}
+
+fn js_error_message(error: JsValue) -> String {
+    match error.dyn_into::<DomException>() {
+        Ok(exception) => exception_message(Some(exception)),
+        Err(error) => describe_error("", &format!("{:?}", error)),
+    }
+}
+
+/// Calls `callback` with the result of the request once it succeeds or fails.
+fn on_request_done(request: &IdbRequest, callback: impl FnOnce(Result<JsValue, String>) + 'static) {
+    // only one of the handlers is called
+    let callback = Rc::new(RefCell::new(Some(callback)));
+    let onsuccess = {
+        let request = request.clone();
+        let callback = callback.clone();
+        Closure::once_into_js(move || {
+            if let Some(callback) = callback.borrow_mut().take() {
+                callback(request.result().map_err(js_error_message));
+            }
+        })
+    };
+    let onerror = {
+        let request = request.clone();
+        Closure::once_into_js(move || {
+            if let Some(callback) = callback.borrow_mut().take() {
+                callback(Err(exception_message(request.error().ok().flatten())));
+            }
+        })
+    };
+    request.set_onsuccess(Some(onsuccess.unchecked_ref()));
+    request.set_onerror(Some(onerror.unchecked_ref()));
+}
+
+/// Calls `callback` once the transaction completes or is aborted
+/// (e.g. when the storage quota is exceeded).
+fn on_transaction_done(
+    transaction: &IdbTransaction,
+    callback: impl FnOnce(Result<(), String>) + 'static,
+) {
+    let callback = Rc::new(RefCell::new(Some(callback)));
+    let oncomplete = {
+        let callback = callback.clone();
+        Closure::once_into_js(move || {
+            if let Some(callback) = callback.borrow_mut().take() {
+                callback(Ok(()));
+            }
+        })
+    };
+    let onabort = {
+        let transaction = transaction.clone();
+        Closure::once_into_js(move || {
+            if let Some(callback) = callback.borrow_mut().take() {
+                callback(Err(exception_message(transaction.error())));
+            }
+        })
+    };
+    transaction.set_oncomplete(Some(oncomplete.unchecked_ref()));
+    transaction.set_onabort(Some(onabort.unchecked_ref()));
+}
+
+/// Opens the database (creating it if needed) and calls `callback` with it.
+fn open(callback: impl FnOnce(Result<IdbDatabase, String>) + 'static) {
+    let factory = match gloo_utils::window().indexed_db() {
+        Ok(Some(factory)) => factory,
+        Ok(None) => return callback(Err(describe_error("InvalidStateError", "no IndexedDB"))),
+        Err(error) => return callback(Err(js_error_message(error))),
+    };
+    let request = match factory.open_with_u32(DB_NAME, DB_VERSION) {
+        Ok(request) => request,
+        Err(error) => return callback(Err(js_error_message(error))),
+    };
+    let onupgradeneeded = {
+        let request = request.clone();
+        Closure::once_into_js(move || {
+            if let Ok(db) = request.result().and_then(|db| db.dyn_into::<IdbDatabase>()) {
+                let _ = db.create_object_store(STORE);
+            }
+        })
+    };
+    request.set_onupgradeneeded(Some(onupgradeneeded.unchecked_ref()));
+    on_request_done(&request, |result| {
+        callback(result.and_then(|db| db.dyn_into::<IdbDatabase>().map_err(js_error_message)))
+    });
+}
+
+/// Opens the object store of the sessions in a new transaction
+/// and calls `f` with it.
+fn with_store(
+    mode: IdbTransactionMode,
+    f: impl FnOnce(Result<(IdbTransaction, IdbObjectStore), String>) + 'static,
+) {
+    open(move |db| {
+        f(db.and_then(|db| {
+            let transaction = db
+                .transaction_with_str_and_mode(STORE, mode)
+                .map_err(js_error_message)?;
+            let store = transaction.object_store(STORE).map_err(js_error_message)?;
+            Ok((transaction, store))
+        }))
+    });
+}
+
+/// Saves the session under the name (replacing the one saved under it, if any).
+pub fn save_session(name: &str, session: &Session, callback: Callback<Result<(), String>>) {
+    let json = match serde_json::to_string(session) {
+        Ok(json) => json,
+        Err(error) => return callback.emit(Err(error.to_string())),
+    };
+    let name = name.to_owned();
+    with_store(IdbTransactionMode::Readwrite, move |store| {
+        let result = store.and_then(|(transaction, store)| {
+            store
+                .put_with_key(&JsValue::from_str(&json), &JsValue::from_str(&name))
+                .map_err(js_error_message)?;
+            Ok(transaction)
+        });
+        match result {
+            Ok(transaction) => {
+                on_transaction_done(&transaction, move |result| callback.emit(result))
+            }
+            Err(error) => callback.emit(Err(error)),
+        }
+    });
+}
+
+/// Deletes the session saved under the name.
+pub fn delete_session(name: &str, callback: Callback<Result<(), String>>) {
+    let name = name.to_owned();
+    with_store(IdbTransactionMode::Readwrite, move |store| {
+        let result = store.and_then(|(transaction, store)| {
+            store
+                .delete(&JsValue::from_str(&name))
+                .map_err(js_error_message)?;
+            Ok(transaction)
+        });
+        match result {
+            Ok(transaction) => {
+                on_transaction_done(&transaction, move |result| callback.emit(result))
+            }
+            Err(error) => callback.emit(Err(error)),
+        }
+    });
+}
+
+/// Lists the names of the saved sessions (in alphabetical order).
+pub fn list_sessions(callback: Callback<Result<Vec<String>, String>>) {
+    with_store(IdbTransactionMode::Readonly, move |store| {
+        match store.and_then(|(_, store)| store.get_all_keys().map_err(js_error_message)) {
+            Ok(request) => on_request_done(&request, move |keys| {
+                callback.emit(keys.map(|keys| {
+                    js_sys::Array::from(&keys)
+                        .iter()
+                        .filter_map(|key| key.as_string())
+                        .collect()
+                }))
+            }),
+            Err(error) => callback.emit(Err(error)),
+        }
+    });
+}
+
+/// Loads the session saved under the name.
+pub fn load_session(name: &str, callback: Callback<Result<Session, String>>) {
+    let name = name.to_owned();
+    with_store(IdbTransactionMode::Readonly, move |store| {
+        let request = store.and_then(|(_, store)| {
+            store
+                .get(&JsValue::from_str(&name))
+                .map_err(js_error_message)
+        });
+        match request {
+            Ok(request) => on_request_done(&request, move |json| {
+                callback.emit(json.and_then(|json| {
+                    let json = json
+                        .as_string()
+                        .ok_or_else(|| format!("no session is saved as {}", name))?;
+                    serde_json::from_str(&json).map_err(|e| e.to_string())
+                }))
+            }),
+            Err(error) => callback.emit(Err(error)),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::describe_error;
+
+    #[test]
+    fn storage_error_messages() {
+        assert_eq!(
+            describe_error("QuotaExceededError", "quota"),
+            "the browser storage is full: delete some saved sessions and try again"
+        );
+        assert!(describe_error("InvalidStateError", "no IndexedDB").contains("unavailable"));
+        assert_eq!(
+            describe_error("DataError", "bad key"),
+            "the browser storage failed: bad key"
+        );
+    }
+}
@@ -0,0 +1,332 @@
+use std::collections::{HashMap, HashSet};
+
+use pest_meta::{
+    ast::RuleType,
+    optimizer::{OptimizedExpr, OptimizedRule},
+};
+
+use crate::debugworker::DebuggerEvent;
+
+/// The name prefix of the marker rules that are only attempted when a rule fails.
+const FAIL_MARKER: &str = "__pest_web_debug_fail_";
+
+/// The name of the rule that wraps the start rule (see `wrap`).
+pub const ROOT_RULE: &str = "__pest_web_debug_root";
+
+/// Adds the `root = { rule }` rule to run instead of the start rule,
+/// so that the parse result has a pair spanning exactly what the start rule matched,
+/// even if the rule is silent or built-in (which don't produce pairs).
+pub fn wrap(mut ast: Vec<OptimizedRule>, rule: &str) -> Vec<OptimizedRule> {
+    ast.push(OptimizedRule {
+        name: ROOT_RULE.to_owned(),
+        ty: RuleType::Normal,
+        expr: OptimizedExpr::Ident(rule.to_owned()),
+    });
+    ast
+}
+
+/// Instruments the grammar, so that the listener is notified when a rule fails:
+/// `rule = { e }` becomes `rule = { e | marker }` where `marker = _{ !"" }`.
+/// The marker never matches, so the grammar still behaves the same.
+pub fn instrument(ast: Vec<OptimizedRule>) -> Vec<OptimizedRule> {
+    let markers = ast
+        .iter()
+        .map(|rule| OptimizedRule {
+            name: format!("{}{}", FAIL_MARKER, rule.name),
+            ty: RuleType::Silent,
+            expr: OptimizedExpr::NegPred(Box::new(OptimizedExpr::Str(String::new()))),
+        })
+        .collect::<Vec<_>>();
+    ast.into_iter()
+        .map(|rule| OptimizedRule {
+            expr: OptimizedExpr::Choice(
+                Box::new(rule.expr),
+                Box::new(OptimizedExpr::Ident(format!(
+                    "{}{}",
+                    FAIL_MARKER, rule.name
+                ))),
+            ),
+            ..rule
+        })
+        .chain(markers)
+        .collect()
+}
+
+/// Calls `f` on the expression and all its sub-expressions (top-down).
+/// Unlike `OptimizedExpr::iter_top_down`, this doesn't skip any expression kinds
+/// and doesn't clone the expressions.
+pub fn visit<'a>(expr: &'a OptimizedExpr, f: &mut impl FnMut(&'a OptimizedExpr)) {
+    f(expr);
+    match expr {
+        OptimizedExpr::Seq(lhs, rhs) | OptimizedExpr::Choice(lhs, rhs) => {
+            visit(lhs, f);
+            visit(rhs, f);
+        }
+        OptimizedExpr::PosPred(expr)
+        | OptimizedExpr::NegPred(expr)
+        | OptimizedExpr::Opt(expr)
+        | OptimizedExpr::Rep(expr)
+        | OptimizedExpr::RepOnce(expr)
+        | OptimizedExpr::Push(expr)
+        | OptimizedExpr::NodeTag(expr, _)
+        | OptimizedExpr::RestoreOnErr(expr) => visit(expr, f),
+        _ => {}
+    }
+}
+
+/// Returns the names of the rules (including built-in ones)
+/// that are referenced in each rule's expression.
+pub fn references(ast: &[OptimizedRule]) -> HashMap<String, HashSet<String>> {
+    ast.iter()
+        .map(|rule| {
+            let mut referenced = HashSet::new();
+            visit(&rule.expr, &mut |expr| {
+                if let OptimizedExpr::Ident(name) = expr {
+                    referenced.insert(name.clone());
+                }
+            });
+            (rule.name.clone(), referenced)
+        })
+        .collect()
+}
+
+/// Whether the `callee` rule can be directly attempted from the `caller` rule.
+fn can_call(references: &HashMap<String, HashSet<String>>, caller: &str, callee: &str) -> bool {
+    let implicit = |rule: &str| rule == "WHITESPACE" || rule == "COMMENT";
+    references
+        .get(caller)
+        .is_some_and(|referenced| referenced.contains(callee))
+        || (implicit(callee) && !implicit(caller))
+}
+
+/// The breakpoints configuration used while parsing.
+#[derive(Debug, Clone, Default)]
+pub struct Breakpoints {
+    /// the rules to stop at
+    pub rules: HashSet<String>,
+    /// the (inclusive) position windows of conditional breakpoints
+    pub conditions: HashMap<String, (usize, usize)>,
+    /// the number of hits to ignore per breakpoint
+    pub ignores: HashMap<String, usize>,
+    /// the position before which no breakpoint is hit (e.g. to run to the cursor)
+    pub from_pos: usize,
+}
+
+/// A rule attempt that hasn't been exited yet.
+struct Frame {
+    rule: String,
+    start: usize,
+    /// whether a breakpoint event was recorded for this attempt
+    breakpoint: bool,
+}
+
+/// Collects the debugger events while parsing.
+///
+/// The `pest_vm` listener is only called when a rule is attempted,
+/// so the rule exits are reconstructed:
+/// failures are reported by the marker rules (see `instrument`), while
+/// successful exits are inferred when the next attempted rule can't be
+/// called from the one on the top of the stack.
+/// The positions of successful exits are then corrected from the parse result
+/// where possible.
+pub struct Trace {
+    breakpoints: Breakpoints,
+    references: HashMap<String, HashSet<String>>,
+    /// the number of hits per breakpoint
+    hits: HashMap<String, usize>,
+    stack: Vec<Frame>,
+    /// the position of the last rule attempt
+    last_pos: usize,
+    /// the collected events
+    events: Vec<DebuggerEvent>,
+    /// the maximum number of breakpoint events to collect
+    event_limit: usize,
+    /// the number of breakpoints hit (including the ones over the limit)
+    breakpoint_count: usize,
+    /// the maximum number of listener calls before parsing is aborted
+    step_limit: usize,
+    /// the number of listener calls
+    steps: usize,
+    /// the event indices and start positions of successful rule exits
+    exits: Vec<(usize, usize)>,
+    /// the number of events already taken while parsing (see `take_new`)
+    taken: usize,
+    /// the event indices and positions corrected by `finish`
+    corrections: Vec<(usize, usize)>,
+}
+
+impl Trace {
+    /// Creates a trace for the given breakpoints and the (uninstrumented) grammar
+    /// that collects at most `event_limit` breakpoint events
+    /// and aborts parsing after `step_limit` listener calls.
+    pub fn new(
+        breakpoints: Breakpoints,
+        ast: &[OptimizedRule],
+        event_limit: usize,
+        step_limit: usize,
+    ) -> Self {
+        Self {
+            breakpoints,
+            references: references(ast),
+            hits: HashMap::new(),
+            stack: vec![],
+            last_pos: 0,
+            events: vec![],
+            event_limit,
+            breakpoint_count: 0,
+            step_limit,
+            steps: 0,
+            exits: vec![],
+            taken: 0,
+            corrections: vec![],
+        }
+    }
+
+    /// Handles a listener call.
+    /// Returns `true` if parsing should be terminated.
+    pub fn on_attempt(&mut self, rule: String, pos: usize) -> bool {
+        if rule == ROOT_RULE {
+            return false;
+        }
+        self.steps += 1;
+        if self.steps > self.step_limit {
+            // the VM fails every rule attempted from now on,
+            // so the parsing quickly unwinds
+            return true;
+        }
+        if let Some(failed) = rule.strip_prefix(FAIL_MARKER) {
+            self.fail(failed, pos);
+            return false;
+        }
+        self.last_pos = pos;
+        while let Some(frame) = self.stack.last() {
+            if pos >= frame.start && can_call(&self.references, &frame.rule, &rule) {
+                break;
+            }
+            self.exit(pos);
+        }
+        let mut breakpoint = self.is_hit(&rule, pos);
+        if breakpoint {
+            self.breakpoint_count += 1;
+            // the breakpoints over the limit (and their exits) aren't collected,
+            // so that pathological grammars don't exhaust the memory
+            breakpoint = self.breakpoint_count <= self.event_limit;
+        }
+        if breakpoint {
+            let stack = self
+                .stack
+                .iter()
+                .map(|frame| frame.rule.clone())
+                .chain(std::iter::once(rule.clone()))
+                .collect();
+            self.events
+                .push(DebuggerEvent::Breakpoint(rule.clone(), pos, None, stack));
+        }
+        self.stack.push(Frame {
+            rule,
+            start: pos,
+            breakpoint,
+        });
+        false
+    }
+
+    /// Whether an attempt of a rule at a position should stop the debugger.
+    fn is_hit(&mut self, rule: &str, pos: usize) -> bool {
+        let in_window = self
+            .breakpoints
+            .conditions
+            .get(rule)
+            .is_none_or(|(start, end)| (*start..=*end).contains(&pos));
+        if !self.breakpoints.rules.contains(rule) || !in_window || pos < self.breakpoints.from_pos {
+            return false;
+        }
+        let hit = self.hits.entry(rule.to_owned()).or_default();
+        *hit += 1;
+        *hit > self.breakpoints.ignores.get(rule).copied().unwrap_or(0)
+    }
+
+    /// Pops the top of the stack as successfully matched.
+    fn exit(&mut self, pos: usize) {
+        if let Some(frame) = self.stack.pop() {
+            if frame.breakpoint {
+                self.exits.push((self.events.len(), frame.start));
+                self.events
+                    .push(DebuggerEvent::RuleExit(frame.rule, pos, true));
+            }
+        }
+    }
+
+    /// Pops the failed rule (and its successfully matched children) from the stack.
+    fn fail(&mut self, rule: &str, pos: usize) {
+        if let Some(index) = self.stack.iter().rposition(|frame| frame.rule == rule) {
+            while self.stack.len() > index + 1 {
+                self.exit(pos);
+            }
+            if let Some(frame) = self.stack.pop() {
+                if frame.breakpoint {
+                    self.events
+                        .push(DebuggerEvent::RuleExit(frame.rule, pos, false));
+                }
+            }
+        }
+    }
+
+    /// Returns the total number of breakpoints hit
+    /// if it exceeded the limit of collected events.
+    pub fn truncated(&self) -> Option<usize> {
+        (self.breakpoint_count > self.event_limit).then_some(self.breakpoint_count)
+    }
+
+    /// Returns the step limit if parsing was aborted because of it.
+    pub fn aborted(&self) -> Option<usize> {
+        (self.steps > self.step_limit).then_some(self.step_limit)
+    }
+
+    /// Returns the events collected since the last call, e.g. to stream them while parsing.
+    /// Their end positions are then only corrected by `finish` (see `corrections`).
+    pub fn take_new(&mut self) -> Vec<DebuggerEvent> {
+        let new = self.events[self.taken..].to_vec();
+        self.taken = self.events.len();
+        new
+    }
+
+    /// Returns the number of events taken with `take_new`.
+    pub fn taken(&self) -> usize {
+        self.taken
+    }
+
+    /// Returns the indices and corrected positions of the events changed by `finish`:
+    /// the end positions of the `Breakpoint`s and the positions of the `RuleExit`s.
+    pub fn corrections(&self) -> &[(usize, usize)] {
+        &self.corrections
+    }
+
+    /// Finishes the trace once parsing is done and returns the collected events.
+    /// `spans` maps the rule names and start positions of the parse result's pairs
+    /// to their end positions.
+    pub fn finish(&mut self, spans: &HashMap<(&str, usize), usize>) -> Vec<DebuggerEvent> {
+        while !self.stack.is_empty() {
+            self.exit(self.last_pos);
+        }
+        for (index, event) in self.events.iter_mut().enumerate() {
+            if let DebuggerEvent::Breakpoint(rule, start, end, _) = event {
+                *end = spans.get(&(rule.as_str(), *start)).copied();
+                if let Some(end) = end {
+                    self.corrections.push((index, *end));
+                }
+            }
+        }
+        for (index, start) in self.exits.drain(..) {
+            if let DebuggerEvent::RuleExit(rule, pos, _) = &mut self.events[index] {
+                match spans.get(&(rule.as_str(), start)) {
+                    Some(end) if end != pos => {
+                        *pos = *end;
+                        self.corrections.push((index, *end));
+                    }
+                    _ => {}
+                }
+            }
+        }
+        std::mem::take(&mut self.events)
+    }
+}
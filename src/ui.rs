@@ -0,0 +1,111 @@
+use std::ops::Range;
+
+use yew::prelude::*;
+
+/// The number of rows rendered above and below the visible ones,
+/// so that scrolling doesn't show blank rows before the next render.
+const OVERSCAN: usize = 10;
+
+/// Returns the range of the rows to render for a scrolled viewport:
+/// the visible rows and `OVERSCAN` rows around them.
+pub fn visible_rows(
+    count: usize,
+    row_height: usize,
+    height: usize,
+    scroll_top: usize,
+) -> Range<usize> {
+    let row_height = row_height.max(1);
+    let first = (scroll_top / row_height).saturating_sub(OVERSCAN);
+    let last = (scroll_top + height).div_ceil(row_height) + OVERSCAN;
+    first.min(count)..last.min(count)
+}
+
+/// Renders a scrollable list of `count` rows of `row_height` pixels
+/// in a viewport of `height` pixels scrolled by `scroll_top` pixels.
+/// Only the rows in (or near) the viewport are rendered by `row`,
+/// while spacers take the place of the others,
+/// so that huge lists don't slow the page down.
+pub fn virtual_list(
+    class: &'static str,
+    count: usize,
+    row_height: usize,
+    height: usize,
+    scroll_top: usize,
+    onscroll: Callback<Event>,
+    row: impl Fn(usize) -> Html,
+) -> Html {
+    let rows = visible_rows(count, row_height, height, scroll_top);
+    let before = rows.start * row_height;
+    let after = (count - rows.end) * row_height;
+    html! {
+        <div class={class} style={format!("height:{}px; overflow-y:auto", height)} {onscroll}>
+            <div style={format!("height:{}px", before)}></div>
+            {rows.map(|index| html! {
+                <div style={format!("height:{}px", row_height)}>{row(index)}</div>
+            }).collect::<Html>()}
+            <div style={format!("height:{}px", after)}></div>
+        </div>
+    }
+}
+
+/// The indentation (in pixels) of a timeline row per level of rule calls.
+const DEPTH_INDENT: usize = 12;
+/// The depth of the rule calls beyond which the timeline rows aren't indented further,
+/// so that the rows of deeply recursive rules stay readable.
+const MAX_INDENTED_DEPTH: usize = 24;
+
+/// Returns the indentation (in pixels) of a timeline row of a rule call at `depth`
+/// (1 for the outermost rule), and whether it's capped.
+pub fn depth_indent(depth: usize) -> (usize, bool) {
+    let level = depth.saturating_sub(1);
+    (
+        level.min(MAX_INDENTED_DEPTH) * DEPTH_INDENT,
+        level > MAX_INDENTED_DEPTH,
+    )
+}
+
+/// The smallest share of the width either pane of a split can be resized to.
+const MIN_SPLIT_RATIO: f64 = 0.2;
+
+/// Returns the share of the split width given to the first pane
+/// when the divider is dragged to `x` (both panes spanning `width` from `left`).
+pub fn split_ratio(x: f64, left: f64, width: f64) -> f64 {
+    if width <= 0.0 {
+        return 0.5;
+    }
+    ((x - left) / width).clamp(MIN_SPLIT_RATIO, 1.0 - MIN_SPLIT_RATIO)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{depth_indent, split_ratio, visible_rows};
+
+    #[test]
+    fn visible_rows_of_scrolled_list() {
+        // 10 rows are visible
+        assert_eq!(visible_rows(1000, 20, 200, 0), 0..20);
+        assert_eq!(visible_rows(1000, 20, 200, 2000), 90..120);
+        assert_eq!(visible_rows(1000, 20, 200, 19_800), 980..1000);
+        assert_eq!(visible_rows(5, 20, 200, 0), 0..5);
+        assert_eq!(visible_rows(0, 20, 200, 0), 0..0);
+    }
+
+    #[test]
+    fn timeline_indentation() {
+        assert_eq!(depth_indent(0), (0, false));
+        assert_eq!(depth_indent(1), (0, false));
+        assert_eq!(depth_indent(3), (24, false));
+        assert_eq!(depth_indent(25), (288, false));
+        assert_eq!(depth_indent(100), (288, true));
+    }
+
+    #[test]
+    fn dragged_split_ratio() {
+        assert_eq!(split_ratio(300.0, 100.0, 400.0), 0.5);
+        assert_eq!(split_ratio(200.0, 100.0, 400.0), 0.25);
+        // neither pane can be collapsed
+        assert_eq!(split_ratio(0.0, 100.0, 400.0), 0.2);
+        assert_eq!(split_ratio(1000.0, 100.0, 400.0), 0.8);
+        assert_eq!(split_ratio(100.0, 100.0, 0.0), 0.5);
+    }
+}